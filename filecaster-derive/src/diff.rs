@@ -0,0 +1,112 @@
+use crate::from_file::with_bounds;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Error, FieldsNamed, Generics, Ident, Result};
+
+/// Generate the `Diff` impls of the struct and its shadow, comparing every
+/// field and recursing through nested types. The values of secret fields are
+/// redacted. Each impl only exists if every field type, or shadow, implements
+/// `Diff`.
+pub fn impl_diff(
+    name: &Ident,
+    file_ident: &Ident,
+    generics: &Generics,
+    fields: &FieldsNamed,
+    secrets: &[bool],
+) -> Result<TokenStream> {
+    let types = fields
+        .named
+        .iter()
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+    let generics_struct = with_bounds(
+        generics,
+        types.iter().map(|ty| quote! { #ty: filecaster::Diff }),
+    );
+    let generics_shadow = with_bounds(
+        generics,
+        types
+            .iter()
+            .map(|ty| quote! { <#ty as filecaster::FromFile>::Shadow: filecaster::Diff }),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics_struct.split_for_impl();
+    let (shadow_impl_generics, _, shadow_where_clause) = generics_shadow.split_for_impl();
+
+    let mut field_diffs = Vec::new();
+    let mut shadow_diffs = Vec::new();
+    for (field, &secret) in fields.named.iter().zip(secrets) {
+        let ident = field
+            .ident
+            .as_ref()
+            .ok_or_else(|| Error::new_spanned(field, "Expected named fields"))?;
+        let ty = &field.ty;
+        let key = ident.unraw().to_string();
+        let field_diff = quote! {
+            <#ty as filecaster::Diff>::diff_into(
+                &self.#ident,
+                &new.#ident,
+                &filecaster::join_path(path, #key),
+                changes,
+            );
+        };
+        let shadow_diff = quote! {
+            <<#ty as filecaster::FromFile>::Shadow as filecaster::Diff>::diff_optional_into(
+                self.#ident.as_ref(),
+                new.#ident.as_ref(),
+                &filecaster::join_path(path, #key),
+                changes,
+            );
+        };
+        field_diffs.push(redact(field_diff, secret));
+        shadow_diffs.push(redact(shadow_diff, secret));
+    }
+
+    Ok(quote! {
+        impl #impl_generics filecaster::Diff for #name #ty_generics #where_clause {
+            fn diff_into(
+                &self,
+                new: &Self,
+                path: &str,
+                changes: &mut Vec<filecaster::FieldChange>,
+            ) {
+                #(#field_diffs)*
+            }
+        }
+
+        impl #shadow_impl_generics filecaster::Diff for #file_ident #ty_generics #shadow_where_clause {
+            fn diff_into(
+                &self,
+                new: &Self,
+                path: &str,
+                changes: &mut Vec<filecaster::FieldChange>,
+            ) {
+                #(#shadow_diffs)*
+            }
+
+            // An unset section compares like one with every field unset.
+            fn diff_optional_into(
+                old: Option<&Self>,
+                new: Option<&Self>,
+                path: &str,
+                changes: &mut Vec<filecaster::FieldChange>,
+            ) {
+                let empty = Self::default();
+                old.unwrap_or(&empty)
+                    .diff_into(new.unwrap_or(&empty), path, changes);
+            }
+        }
+    })
+}
+
+/// Masks the changes `diff` appends if the field is a secret.
+fn redact(diff: TokenStream, secret: bool) -> TokenStream {
+    if !secret {
+        return diff;
+    }
+    quote! {
+        let start = changes.len();
+        #diff
+        filecaster::redact(&mut changes[start..]);
+    }
+}
@@ -1,12 +1,19 @@
+use crate::diff::impl_diff;
+use crate::meta::{compact, impl_meta};
+use crate::schema::{FieldSchema, impl_json_schema};
+use crate::shadow::{FieldDefault, impl_to_shadow};
+use crate::validate::{ContainerCheck, Validator, container_checks, field_checks};
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
+use syn::ext::IdentExt;
 use syn::{
     Attribute, Data, DeriveInput, Error, Expr, Field, Fields, FieldsNamed, GenericParam, Generics,
-    Ident, Lit, Meta, MetaList, Result, Type, parse_quote,
+    Ident, Lit, LitStr, Meta, MetaList, Result, Type, parse_quote,
 };
 
 const WITH_MERGE: bool = cfg!(feature = "merge");
 const WITH_SERDE: bool = cfg!(feature = "serde");
+const WITH_SCHEMA: bool = cfg!(feature = "schema");
 
 /// Entry point: generate the shadow struct + `FromFile` impls.
 pub fn impl_from_file(input: &DeriveInput) -> Result<TokenStream> {
@@ -18,38 +25,167 @@ pub fn impl_from_file(input: &DeriveInput) -> Result<TokenStream> {
     let file_ident = format_ident!("{name}File");
 
     let fields = extract_named_fields(input)?;
+    let container = parse_container_attrs(&input.attrs)?;
     let (field_assignments, file_fields) = process_fields(fields)?;
+    let locals = fields
+        .named
+        .iter()
+        .filter_map(|f| f.ident.as_ref())
+        .map(local_ident)
+        .collect::<Vec<_>>();
+    let field_idents = fields.named.iter().filter_map(|f| f.ident.as_ref());
+
+    let field_attrs = fields
+        .named
+        .iter()
+        .map(|field| parse_field_attrs(&field.attrs))
+        .collect::<Result<Vec<_>>>()?;
+    let secrets = field_attrs
+        .iter()
+        .map(|attrs| attrs.secret)
+        .collect::<Vec<_>>();
+    let redacted = secrets.contains(&true);
+    let derive_clause = build_derive_clause(!redacted);
+    let debug_impl = if redacted {
+        build_debug_impl(&file_ident, &generics, fields, &secrets)?
+    } else {
+        quote! {}
+    };
+    let serde_attrs = build_serde_attrs(&container);
+    let diff_impl = impl_diff(name, &file_ident, &generics, fields, &secrets)?;
+    let meta_options = field_attrs
+        .iter()
+        .map(build_meta_options)
+        .collect::<Vec<_>>();
+    let defaults = field_attrs
+        .iter()
+        .map(|attrs| match &attrs.default {
+            Some(expr) => FieldDefault::Expr(parse_default(expr.clone())),
+            None if attrs.required => FieldDefault::Required,
+            None => FieldDefault::FromFile,
+        })
+        .collect::<Vec<_>>();
+    let meta_impl = impl_meta(
+        name,
+        &generics,
+        fields,
+        &meta_options,
+        &build_struct_meta_options(&container),
+    )?;
+    let shadow_impl = impl_to_shadow(name, &file_ident, &generics, fields, &defaults, &secrets)?;
+    let checks = container_checks(&container.checks);
+    // `From` cannot fail, so it is only offered where `from_file` cannot panic
+    // on the struct's own fields.
+    let infallible = container.checks.is_empty()
+        && field_attrs
+            .iter()
+            .all(|attrs| !attrs.required && attrs.validators.is_empty());
+    let from_impls = if infallible {
+        quote! {
+            impl #impl_generics From<Option<#file_ident #ty_generics>> for #name #ty_generics #where_clause {
+                fn from(value: Option<#file_ident #ty_generics>) -> Self {
+                    <Self as filecaster::FromFile>::from_file(value)
+                }
+            }
 
-    let derive_clause = build_derive_clause();
+            impl #impl_generics From<#file_ident #ty_generics> for #name #ty_generics #where_clause {
+                fn from(value: #file_ident #ty_generics) -> Self {
+                    <Self as filecaster::FromFile>::from_file(Some(value))
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let path_impl = if WITH_SERDE {
+        quote! {
+            impl #impl_generics filecaster::PathAccess for #file_ident #ty_generics #where_clause {
+                fn path_meta() -> filecaster::StructMeta {
+                    <#name #ty_generics as filecaster::ConfigMeta>::config_meta()
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let schema_impl = if WITH_SCHEMA {
+        let schemas = field_attrs
+            .iter()
+            .zip(&defaults)
+            .map(|(attrs, default)| FieldSchema {
+                doc: attrs.doc.as_deref(),
+                default,
+                required: attrs.required,
+                validators: &attrs.validators,
+                aliases: &attrs.aliases,
+                deprecated: attrs.deprecated.is_some(),
+                secret: attrs.secret,
+            })
+            .collect::<Vec<_>>();
+        let deny = container
+            .unknown_keys
+            .as_ref()
+            .is_some_and(|policy| policy == "Deny");
+        impl_json_schema(
+            name,
+            &generics,
+            fields,
+            &schemas,
+            container.doc.as_deref(),
+            deny,
+        )?
+    } else {
+        quote! {}
+    };
 
     Ok(quote! {
         #derive_clause
+        #serde_attrs
         #vis struct #file_ident #ty_generics #where_clause {
             #(#file_fields),*
         }
 
+        #debug_impl
+
         impl #impl_generics filecaster::FromFile for #name #ty_generics #where_clause {
             type Shadow = #file_ident #ty_generics;
 
             fn from_file(file: Option<Self::Shadow>) -> Self {
+                Self::try_from_file(file).unwrap_or_else(|e| panic!("{e}"))
+            }
+
+            fn try_from_file(file: Option<Self::Shadow>) -> Result<Self, filecaster::Error> {
                 let file = file.unwrap_or_default();
-                Self {
-                    #(#field_assignments),*
+                let mut __errors = Vec::<filecaster::Error>::new();
+                #(#field_assignments)*
+                match (#(#locals,)*) {
+                    (#(Some(#locals),)*) if __errors.is_empty() => {
+                        let __value = Self {
+                            #(#field_idents: #locals),*
+                        };
+                        #checks
+                        if __errors.is_empty() {
+                            Ok(__value)
+                        } else {
+                            Err(filecaster::Error::multiple(__errors))
+                        }
+                    }
+                    _ => Err(filecaster::Error::multiple(__errors)),
                 }
             }
-        }
 
-        impl #impl_generics From<Option<#file_ident #ty_generics>> for #name #ty_generics #where_clause {
-            fn from(value: Option<#file_ident #ty_generics>) -> Self {
-                <Self as filecaster::FromFile>::from_file(value)
+            fn meta() -> Option<filecaster::StructMeta> {
+                Some(<Self as filecaster::ConfigMeta>::config_meta())
             }
         }
 
-        impl #impl_generics From<#file_ident #ty_generics> for #name #ty_generics #where_clause {
-            fn from(value: #file_ident #ty_generics) -> Self {
-                <Self as filecaster::FromFile>::from_file(Some(value))
-            }
-        }
+        #meta_impl
+        #from_impls
+
+        #diff_impl
+        #shadow_impl
+        #path_impl
+        #schema_impl
     })
 }
 
@@ -80,13 +216,19 @@ fn build_file_field(field: &Field) -> Result<(TokenStream, TokenStream)> {
         .ok_or_else(|| Error::new_spanned(field, "Expected named fields"))?;
     let ty = &field.ty;
 
-    let default_override = parse_from_file_default_attr(&field.attrs)?;
+    let attrs = parse_field_attrs(&field.attrs)?;
 
-    let field_attrs = if WITH_MERGE {
+    let mut field_attrs = if WITH_MERGE {
         quote! { #[merge(strategy = merge::option::overwrite_none)] }
     } else {
         quote! {}
     };
+    if WITH_SERDE {
+        field_attrs.extend(quote! { #[serde(skip_serializing_if = "Option::is_none")] });
+        for alias in &attrs.aliases {
+            field_attrs.extend(quote! { #[serde(alias = #alias)] });
+        }
+    }
 
     // Nested struct -> delegate to its own `FromFile` impl
     let shadow_ty = quote! { <#ty as filecaster::FromFile>::Shadow };
@@ -95,22 +237,99 @@ fn build_file_field(field: &Field) -> Result<(TokenStream, TokenStream)> {
         pub #ident: Option<#shadow_ty>
     };
 
-    let assign = build_file_assing(ident, ty, default_override);
+    let assign = build_file_assing(ident, ty, attrs);
 
     Ok((field_decl, assign))
 }
 
-fn build_file_assing(ident: &Ident, ty: &Type, default_override: Option<Expr>) -> TokenStream {
-    if let Some(expr) = default_override {
-        return quote! {
-            #ident: file.#ident.map(|inner| <#ty as filecaster::FromFile>::from_file(Some(inner))).unwrap_or(#expr)
-        };
-    }
+/// Name of the local holding the resolved `Option<T>` of a field, kept apart
+/// from the `file` and `__errors` locals of the generated `try_from_file`.
+fn local_ident(ident: &Ident) -> Ident {
+    format_ident!("__field_{}", ident.unraw())
+}
+
+/// Binds `Some(value)` for a resolved field, or `None` after recording why
+/// it could not be resolved, then validates the value.
+fn build_file_assing(ident: &Ident, ty: &Type, attrs: FieldAttrs) -> TokenStream {
+    let key = ident.unraw().to_string();
+    let local = local_ident(ident);
+    let resolve = |shadow: TokenStream| {
+        quote! {
+            filecaster::collect_error(
+                <#ty as filecaster::FromFile>::try_from_file(#shadow).map_err(|e| e.at(#key)),
+                &mut __errors,
+            )
+        }
+    };
+    let value = if let Some(expr) = attrs.default.map(parse_default) {
+        let present = resolve(quote! { Some(inner) });
+        quote! {
+            match file.#ident {
+                Some(inner) => #present,
+                None => Some(#expr),
+            }
+        }
+    } else if attrs.required {
+        let present = resolve(quote! { Some(inner) });
+        quote! {
+            match file.#ident {
+                Some(inner) => #present,
+                None => {
+                    __errors.push(filecaster::Error::Missing {
+                        paths: vec![#key.to_string()],
+                    });
+                    None
+                }
+            }
+        }
+    } else {
+        resolve(quote! { file.#ident })
+    };
+    let checks = field_checks(&key, &local, ty, &attrs.validators, attrs.secret);
     quote! {
-        #ident: <#ty as filecaster::FromFile>::from_file(file.#ident)
+        let #local = #value;
+        #checks
     }
 }
 
+/// Builder calls adding the unknown-key policy and doc comment of the struct
+/// to its `filecaster::StructMeta`.
+fn build_struct_meta_options(container: &ContainerAttrs) -> TokenStream {
+    let policy = container.unknown_keys.as_ref().map(|policy| {
+        quote! { .unknown_keys(filecaster::UnknownKeys::#policy) }
+    });
+    let doc = container.doc.as_ref().map(|doc| quote! { .doc(#doc) });
+    quote! { #policy #doc }
+}
+
+/// Builder calls adding the doc comment, default expression, `required` and
+/// `secret` flags, validation options, aliases, deprecation note and
+/// environment variable of a field to its `filecaster::FieldMeta`.
+fn build_meta_options(attrs: &FieldAttrs) -> TokenStream {
+    let doc = attrs.doc.as_ref().map(|doc| quote! { .doc(#doc) });
+    let required = attrs.required.then(|| quote! { .required() });
+    let secret = attrs.secret.then(|| quote! { .secret() });
+    let constraints = attrs
+        .validators
+        .iter()
+        .map(Validator::describe)
+        .collect::<Vec<_>>();
+    let constraints =
+        (!constraints.is_empty()).then(|| quote! { .constraints(&[#(#constraints),*]) });
+    let default = attrs.default.as_ref().map(|expr| {
+        let text = compact(expr);
+        quote! { .default(#text) }
+    });
+    let aliases = &attrs.aliases;
+    let aliases = (!aliases.is_empty()).then(|| quote! { .aliases(&[#(#aliases),*]) });
+    let deprecated = attrs
+        .deprecated
+        .as_ref()
+        .map(|note| quote! { .deprecated(#note) });
+    let env = attrs.env.as_ref().map(|name| quote! { .env(#name) });
+    quote! { #doc #default #required #secret #constraints #aliases #deprecated #env }
+}
+
 /// Process all fields
 fn process_fields(fields: &FieldsNamed) -> Result<(Vec<TokenStream>, Vec<TokenStream>)> {
     fields.named.iter().try_fold(
@@ -124,9 +343,13 @@ fn process_fields(fields: &FieldsNamed) -> Result<(Vec<TokenStream>, Vec<TokenSt
     )
 }
 
-/// Derive clause for the shadow struct
-fn build_derive_clause() -> TokenStream {
-    let mut traits = vec![quote! {Debug}, quote! {Clone}, quote! {Default}];
+/// Derive clause for the shadow struct; `Debug` is implemented by hand
+/// when secrets need hiding.
+fn build_derive_clause(debug: bool) -> TokenStream {
+    let mut traits = vec![quote! {Clone}, quote! {Default}];
+    if debug {
+        traits.insert(0, quote! {Debug});
+    }
     if WITH_SERDE {
         traits.extend([quote! { serde::Deserialize }, quote! { serde::Serialize }]);
     }
@@ -138,6 +361,61 @@ fn build_derive_clause() -> TokenStream {
     quote! { #[derive( #(#traits),* )] }
 }
 
+/// `Debug` impl of the shadow struct, showing `filecaster::REDACTED` for
+/// the secret fields that are set.
+fn build_debug_impl(
+    file_ident: &Ident,
+    generics: &Generics,
+    fields: &FieldsNamed,
+    secrets: &[bool],
+) -> Result<TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut bounds = Vec::new();
+    let entries = fields
+        .named
+        .iter()
+        .zip(secrets)
+        .map(|(field, &secret)| {
+            let ident = field
+                .ident
+                .as_ref()
+                .ok_or_else(|| Error::new_spanned(field, "Expected named fields"))?;
+            let ty = &field.ty;
+            let key = ident.unraw().to_string();
+            if secret {
+                return Ok(quote! {
+                    .field(#key, &self.#ident.as_ref().map(|_| filecaster::REDACTED))
+                });
+            }
+            bounds
+                .push(quote! { Option<<#ty as filecaster::FromFile>::Shadow>: ::std::fmt::Debug });
+            Ok(quote! { .field(#key, &self.#ident) })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let predicates = where_clause.map(|clause| &clause.predicates);
+    let name = file_ident.to_string();
+
+    Ok(quote! {
+        impl #impl_generics ::std::fmt::Debug for #file_ident #ty_generics
+        where
+            #predicates
+            #(#bounds,)*
+        {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(#name)#(#entries)*.finish()
+            }
+        }
+    })
+}
+
+/// Container attributes for the shadow struct
+fn build_serde_attrs(container: &ContainerAttrs) -> TokenStream {
+    match &container.unknown_keys {
+        Some(policy) if WITH_SERDE && policy == "Deny" => quote! { #[serde(deny_unknown_fields)] },
+        _ => quote! {},
+    }
+}
+
 /// Add Default bound to every generic parameter
 fn add_trait_bounds(mut generics: Generics) -> Generics {
     for param in &mut generics.params {
@@ -148,45 +426,173 @@ fn add_trait_bounds(mut generics: Generics) -> Generics {
     generics
 }
 
-/// Attribute parsing: `#[from_file(default = ...)]`
-fn parse_from_file_default_attr(attrs: &[Attribute]) -> Result<Option<Expr>> {
+/// `generics` with a `where` predicate added for each of `bounds`, so that
+/// an impl only exists for the field types that support it.
+///
+/// The predicates are written `for<'__bound> ...`: a plain bound on a
+/// concrete type that does not hold is an error, rather than an impl that
+/// does not apply.
+pub fn with_bounds(generics: &Generics, bounds: impl IntoIterator<Item = TokenStream>) -> Generics {
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    for bound in bounds {
+        where_clause
+            .predicates
+            .push(parse_quote!(for<'__bound> #bound));
+    }
+    generics
+}
+
+/// Options collected from the `#[from_file(...)]` attributes of the struct.
+#[derive(Debug, Default)]
+struct ContainerAttrs {
+    /// Variant of `filecaster::UnknownKeys`.
+    unknown_keys: Option<Ident>,
+    checks: Vec<ContainerCheck>,
+    doc: Option<String>,
+}
+
+/// Attribute parsing: `#[from_file(unknown_keys = "ignore" | "warn" | "deny")]`
+/// and the struct-level constraints of [`ContainerCheck`]
+fn parse_container_attrs(attrs: &[Attribute]) -> Result<ContainerAttrs> {
+    let mut container = ContainerAttrs {
+        doc: parse_doc(attrs),
+        ..ContainerAttrs::default()
+    };
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("from_file"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("unknown_keys") {
+                let value = meta.value()?.parse::<LitStr>()?;
+                let variant = match value.value().as_str() {
+                    "ignore" => "Ignore",
+                    "warn" => "Warn",
+                    "deny" => "Deny",
+                    _ => {
+                        return Err(Error::new_spanned(
+                            value,
+                            "expected \"ignore\", \"warn\" or \"deny\"",
+                        ));
+                    }
+                };
+                container.unknown_keys = Some(Ident::new(variant, value.span()));
+                Ok(())
+            } else if let Some(check) = ContainerCheck::parse(&meta)? {
+                container.checks.push(check);
+                Ok(())
+            } else {
+                Err(meta.error("unknown from_file container attribute"))
+            }
+        })?;
+    }
+    Ok(container)
+}
+
+/// Options collected from the `#[from_file(...)]` attributes of one field.
+#[derive(Debug, Default)]
+struct FieldAttrs {
+    default: Option<Expr>,
+    required: bool,
+    secret: bool,
+    validators: Vec<Validator>,
+    aliases: Vec<LitStr>,
+    deprecated: Option<LitStr>,
+    env: Option<LitStr>,
+    doc: Option<String>,
+}
+
+/// Attribute parsing: `#[from_file(default = ...)]`, `#[from_file(required)]`,
+/// `#[from_file(secret)]`, `#[from_file(alias = "...", deprecated = "...")]`,
+/// `#[from_file(env = "...")]` and the validation options of [`Validator`]
+fn parse_field_attrs(attrs: &[Attribute]) -> Result<FieldAttrs> {
+    let mut field_attrs = FieldAttrs {
+        doc: parse_doc(attrs),
+        ..FieldAttrs::default()
+    };
     for attr in attrs {
         if !attr.path().is_ident("from_file") {
             continue; // Not a #[from_file] attribute, skip it
         }
 
         // Parse the content inside the parentheses of #[from_file(...)]
-        return match &attr.meta {
-            Meta::List(meta_list) => parse_default(meta_list),
-            _ => Err(Error::new_spanned(
-                attr,
-                "Expected #[from_file(default = \"literal\")] or similar",
-            )),
-        };
+        match &attr.meta {
+            Meta::List(meta_list) => parse_nested(meta_list, &mut field_attrs)?,
+            _ => {
+                return Err(Error::new_spanned(
+                    attr,
+                    "Expected #[from_file(default = \"literal\")] or similar",
+                ));
+            }
+        }
     }
-    Ok(None)
+    Ok(field_attrs)
 }
 
-fn parse_default(list: &MetaList) -> Result<Option<Expr>> {
-    let mut default_expr = None;
+fn parse_nested(list: &MetaList, attrs: &mut FieldAttrs) -> Result<()> {
     list.parse_nested_meta(|meta| {
         if meta.path.is_ident("default") {
             let value = meta.value()?;
             let expr = value.parse::<Expr>()?;
-
-            if let Expr::Lit(expr_lit) = &expr
-                && let Lit::Str(lit_str) = &expr_lit.lit
-            {
-                default_expr = Some(parse_quote! {
-                    #lit_str.to_string()
-                });
-                return Ok(());
-            }
-            default_expr = Some(expr);
+            attrs.default = Some(expr);
+        } else if meta.path.is_ident("required") {
+            attrs.required = true;
+        } else if meta.path.is_ident("secret") {
+            attrs.secret = true;
+        } else if meta.path.is_ident("alias") {
+            attrs.aliases.push(meta.value()?.parse()?);
+        } else if meta.path.is_ident("deprecated") {
+            attrs.deprecated = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("env") {
+            attrs.env = Some(meta.value()?.parse()?);
+        } else if let Some(validator) = Validator::parse(&meta)? {
+            attrs.validators.push(validator);
+        } else {
+            return Err(meta.error("unknown from_file attribute"));
         }
         Ok(())
     })?;
-    Ok(default_expr)
+    if attrs.required && attrs.default.is_some() {
+        return Err(Error::new_spanned(
+            list,
+            "`required` and `default` cannot be combined",
+        ));
+    }
+    Ok(())
+}
+
+/// The text of the `///` comments among `attrs`, without the space that
+/// follows each `///`.
+fn parse_doc(attrs: &[Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(text) => Some(text.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').map(str::to_string).unwrap_or(line))
+        .collect::<Vec<_>>();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+fn parse_default(expr: Expr) -> Expr {
+    if let Expr::Lit(expr_lit) = &expr
+        && let Lit::Str(lit_str) = &expr_lit.lit
+    {
+        return parse_quote! {
+            #lit_str.to_string()
+        };
+    }
+    expr
 }
 
 #[cfg(test)]
@@ -229,7 +635,77 @@ mod tests {
     #[test]
     fn parse_default_attrs_none() {
         let attrs: Vec<Attribute> = vec![parse_quote!(#[foo])];
-        assert_none!(parse_from_file_default_attr(&attrs).unwrap());
+        assert_none!(parse_field_attrs(&attrs).unwrap().default);
+    }
+
+    #[test]
+    fn parse_required_attr() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(required)])];
+        let parsed = parse_field_attrs(&attrs).unwrap();
+        assert!(parsed.required);
+        assert_none!(parsed.default);
+    }
+
+    #[test]
+    fn parse_secret_attr() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(secret, default = "changeme")])];
+        let parsed = parse_field_attrs(&attrs).unwrap();
+        assert!(parsed.secret);
+        assert!(!parse_field_attrs(&[]).unwrap().secret);
+    }
+
+    #[test]
+    fn parse_validation_attrs() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(
+            #[from_file(default = 80, range = 1..=65535, one_of = [80, 443], validate = check)]
+        )];
+        let parsed = parse_field_attrs(&attrs).unwrap();
+        assert!(parsed.default.is_some());
+        assert_eq!(parsed.validators.len(), 3);
+    }
+
+    #[test]
+    fn parse_alias_attrs() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(
+            #[from_file(alias = "old", alias = "older", deprecated = "use `new` instead")]
+        )];
+        let parsed = parse_field_attrs(&attrs).unwrap();
+        assert_eq!(parsed.aliases.len(), 2);
+        assert_eq!(parsed.deprecated.unwrap().value(), "use `new` instead");
+
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(env = "APP_PORT")])];
+        assert_eq!(
+            parse_field_attrs(&attrs).unwrap().env.unwrap().value(),
+            "APP_PORT"
+        );
+
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(alias = old)])];
+        assert_err!(parse_field_attrs(&attrs));
+    }
+
+    #[test]
+    fn parse_doc_comments() {
+        let attrs: Vec<Attribute> = vec![
+            parse_quote!(#[doc = " Port to listen on."]),
+            parse_quote!(#[doc = ""]),
+            parse_quote!(#[doc = "   Indented."]),
+            parse_quote!(#[from_file(default = 80)]),
+        ];
+        let parsed = parse_field_attrs(&attrs).unwrap();
+        assert_eq!(parsed.doc.unwrap(), "Port to listen on.\n\n  Indented.");
+        assert_none!(parse_doc(&[parse_quote!(#[doc = " "])]));
+    }
+
+    #[test]
+    fn parse_required_with_default_is_an_error() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(required, default = 1)])];
+        assert_err!(parse_field_attrs(&attrs));
+    }
+
+    #[test]
+    fn parse_unknown_attr_is_an_error() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(requried)])];
+        assert_err!(parse_field_attrs(&attrs));
     }
 
     #[test]
@@ -247,6 +723,35 @@ mod tests {
         assert_eq!(file_fields.len(), 2);
     }
 
+    #[test]
+    fn parse_container_unknown_keys() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(unknown_keys = "warn")])];
+        let parsed = parse_container_attrs(&attrs).unwrap();
+        assert_eq!(parsed.unknown_keys.unwrap(), "Warn");
+
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(unknown_keys = "loud")])];
+        assert_err!(parse_container_attrs(&attrs));
+    }
+
+    #[test]
+    fn parse_container_checks() {
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(
+            validate = Self::check,
+            exactly_one_of(tls.cert, tls.acme),
+            requires(key, cert),
+            check(min <= max)
+        )])];
+        let parsed = parse_container_attrs(&attrs).unwrap();
+        assert_eq!(parsed.checks.len(), 4);
+
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(check(min + max))])];
+        assert_err!(parse_container_attrs(&attrs));
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(exactly_one_of(a))])];
+        assert_err!(parse_container_attrs(&attrs));
+        let attrs: Vec<Attribute> = vec![parse_quote!(#[from_file(requires(a))])];
+        assert_err!(parse_container_attrs(&attrs));
+    }
+
     #[test]
     fn add_trait_bouds_appends_default() {
         let gens: Generics = parse_quote!(<T, U>);
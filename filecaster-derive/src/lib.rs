@@ -23,6 +23,35 @@
 //!     -   An expression you supply via `#[from_file(default = ...)]`.
 //!     -   `Default::default()` (if no `default` attribute is provided, requiring `T: Default`).
 //!
+//!     It also provides `try_from_file`, which returns a `filecaster::Error`
+//!     naming the failing field (e.g. `server.port`) instead of panicking.
+//!     Every field is resolved before returning, so all failing fields are
+//!     reported together.
+//!
+//!     `From<YourStructFile>` and `From<Option<YourStructFile>>` call
+//!     `from_file`. They are left out for structs with `required` fields or
+//!     validation attributes, whose conversion can fail; a nested struct
+//!     that fails still makes them panic.
+//! 3.  Implementations of `filecaster::Diff` for the struct and its shadow,
+//!     comparing two instances field by field and recursing into nested
+//!     `FromFile` structs. They only exist if every leaf field type
+//!     implements `PartialEq` and `Debug`.
+//! 4.  An implementation of `filecaster::ToShadow` and `From<&T>` for the
+//!     shadow, turning a resolved instance back into a shadow with every
+//!     field set; `default_shadow` holds the defaults themselves, resolved
+//!     field by field. Unset shadow fields are skipped when serializing.
+//!     They only exist if every field type implements `ToShadow`, as leaf
+//!     values and derived structs do. `filecaster::MinimalShadow` sets only
+//!     the fields that differ from their defaults; it also needs every field
+//!     type to implement `PartialEq`.
+//! 5.  An implementation of `filecaster::ConfigMeta`, describing every
+//!     field (key, type, doc comment, default expression and attributes)
+//!     and recursing into nested structs. `FromFile::meta` returns it.
+//! 6.  With the `schema` feature, an implementation of
+//!     `filecaster::schema::JsonSchema` describing the shadow: field types,
+//!     doc comments, defaults, required fields and validation constraints.
+//!     It only exists if every field type implements `JsonSchema`.
+//!
 //! ## Optional per-field defaults
 //!
 //! Use a `#[from_file(default = <expr>)]` attribute on any field to override
@@ -30,6 +59,83 @@
 //! context. If you omit it, the macro will require the field's type to implement
 //! `Default` and will call `Default::default()`.
 //!
+//! ## Required fields
+//!
+//! Mark a field with `#[from_file(required)]` when it has no sensible
+//! default. `try_from_file` then fails with `filecaster::Error::Missing`,
+//! listing the path of every missing required field, including those of
+//! nested structs (e.g. `database.url`). `required` cannot be combined with
+//! `default`.
+//!
+//! ## Validation
+//!
+//! Fields can be checked once they are resolved, defaults included:
+//!
+//! - `range = 1..=65535`: the value lies within a range.
+//! - `min_len = 1`, `max_len = 64`, `non_empty`: length of a string (in
+//!   characters) or collection.
+//! - `one_of = ["debug", "info"]`: the value equals one of the options.
+//! - `regex = "^[a-z]+$"`: a string matches a pattern (needs the `regex`
+//!   feature of `filecaster`).
+//! - `validate = path::to::check`: calls `check(&value)`, which returns
+//!   `Result<(), E>` with `E: Display`.
+//!
+//! Failures are reported by `try_from_file` with the field's path, together
+//! with any other failing field. On an `Option<T>` field, all but `validate`
+//! check the value inside `Some` and let `None` pass; `validate` is given the
+//! `Option` itself.
+//!
+//! Constraints spanning several fields go on the struct and run once it is
+//! fully resolved, nested structs included. Fields are named by dotted paths:
+//!
+//! - `exactly_one_of(tls.cert_path, tls.acme)`: exactly one of the fields is
+//!   set (`Some`, or `true` for a `bool`).
+//! - `requires(key_path, cert_path)`: if the first field is set, so are the
+//!   others.
+//! - `check(min_workers <= max_workers)`: two fields compare as stated, with
+//!   any of `<`, `<=`, `>`, `>=`, `==` or `!=`.
+//! - `validate = Self::check`: calls `check(&self)`, like the field option.
+//!
+//! Their failures are reported with the path of the struct.
+//!
+//! ## Renamed and deprecated keys
+//!
+//! `#[from_file(alias = "old_name")]` (repeatable) lets the field also be
+//! set through a former key; the shadow gets a matching `#[serde(alias)]`.
+//! `deprecated = "use `new_name` instead"` adds a note to the warnings
+//! `filecaster::Loader` reports for aliases, or marks the field itself as
+//! deprecated if it has none.
+//!
+//! ## Environment variables
+//!
+//! `#[from_file(env = "APP_PORT")]` names the environment variable that sets
+//! the field. `filecaster::Loader::env` layers it over the files, coercing
+//! its string into the field's type; it is also recorded in
+//! `ConfigMeta::config_meta` and listed by `filecaster::Reference`.
+//!
+//! ## Secrets
+//!
+//! `#[from_file(secret)]` hides a field's value: the shadow's `Debug` output,
+//! `Diff` changes and validation errors show `filecaster::REDACTED` (`***`)
+//! instead, `ToShadow::to_redacted_shadow` leaves it unset, and templates,
+//! schemas and references omit its default. The struct's own `Debug` impl is yours,
+//! so derive it only if it may show the value.
+//!
+//! ## Doc comments
+//!
+//! Doc comments on the struct and its fields are recorded in
+//! `ConfigMeta::config_meta`, from which `filecaster::Template` writes the
+//! comments of generated configuration files.
+//!
+//! ## Unknown keys
+//!
+//! `#[from_file(unknown_keys = "warn")]` (or `"deny"`, `"ignore"`) on the
+//! struct sets how `filecaster::Loader` treats keys that none of its fields
+//! accept, overriding the loader-wide `Loader::unknown_keys` policy. With
+//! `"deny"`, the shadow also gets `#[serde(deny_unknown_fields)]`. The derive
+//! implements `FromFile::meta` to describe the fields this check runs
+//! against.
+//!
 //! ## Example
 //!
 //! ```rust
@@ -82,11 +188,15 @@
 //!
 //! -   `serde`: Enables `serde` serialization/deserialization support for the
 //!     generated shadow structs. This is typically required to deserialize
-//!     your configuration from file formats like JSON, TOML, or YAML.
+//!     your configuration from file formats like JSON, TOML, or YAML. The
+//!     shadow also implements `filecaster::PathAccess`, to get and set its
+//!     fields by dotted key path.
 //! -   `merge`: If enabled, the generated shadow struct will also derive
 //!     `merge::Merge`. This allows you to layer multiple partial configuration
 //!     files together before calling `.from_file(...)`. Any field-level
 //!     `#[merge(...)]` attributes will be respected.
+//! -   `schema`: Implements `filecaster::schema::JsonSchema` for the struct,
+//!     provided its leaf field types implement it as well.
 //!
 //! ## Limitations
 //!
@@ -94,7 +204,12 @@
 //! -   All fields without a `#[from_file(default = ...)]` attribute must
 //!     implement the `Default` trait.
 
+mod diff;
 mod from_file;
+mod meta;
+mod schema;
+mod shadow;
+mod validate;
 
 pub(crate) use from_file::impl_from_file;
 use proc_macro::TokenStream;
@@ -0,0 +1,111 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote};
+use syn::ext::IdentExt;
+use syn::{Error, FieldsNamed, Generics, Ident, Result};
+
+/// Generate the `filecaster::ConfigMeta` impl, describing every field and
+/// recursing through nested types.
+///
+/// `options` holds the builder calls to append to each field's `FieldMeta`,
+/// and `struct_options` those to append to the `StructMeta`.
+pub fn impl_meta(
+    name: &Ident,
+    generics: &Generics,
+    fields: &FieldsNamed,
+    options: &[TokenStream],
+    struct_options: &TokenStream,
+) -> Result<TokenStream> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let field_metas = fields
+        .named
+        .iter()
+        .zip(options)
+        .map(|(field, options)| {
+            let ident = field
+                .ident
+                .as_ref()
+                .ok_or_else(|| Error::new_spanned(field, "Expected named fields"))?;
+            let ty = &field.ty;
+            let key = ident.unraw().to_string();
+            let type_name = compact(ty);
+            Ok(quote! {
+                filecaster::FieldMeta::new(#key, <#ty as filecaster::FromFile>::meta())
+                    .type_name(#type_name)
+                    #options
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let struct_name = name.unraw().to_string();
+
+    Ok(quote! {
+        impl #impl_generics filecaster::ConfigMeta for #name #ty_generics #where_clause {
+            fn config_meta() -> filecaster::StructMeta {
+                filecaster::StructMeta::new(vec![#(#field_metas),*])
+                    .type_name(#struct_name)
+                    #struct_options
+            }
+        }
+    })
+}
+
+/// `tokens` as they would be written, e.g. `Vec<String>` rather than the
+/// `Vec < String >` of their token stream. String literals are kept as is.
+pub fn compact(tokens: &impl ToTokens) -> String {
+    let tokens = tokens.to_token_stream().to_string();
+    let mut name = String::with_capacity(tokens.len());
+    let mut chars = tokens.chars().peekable();
+    let (mut quoted, mut escaped) = (false, false);
+    while let Some(c) = chars.next() {
+        if quoted {
+            quoted = escaped || c != '"';
+            escaped = !escaped && c == '\\';
+            name.push(c);
+            continue;
+        }
+        let next = chars.peek().copied().unwrap_or(' ');
+        let previous = name.chars().last().unwrap_or(' ');
+        let word = |c: char| c.is_alphanumeric() || c == '_';
+        if c == ' ' && !(word(previous) && word(next)) && previous != ',' && previous != ';' {
+            continue;
+        }
+        quoted = c == '"';
+        name.push(c);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::{Expr, Type, parse_quote};
+
+    #[test]
+    fn type_names_read_like_source() {
+        let names: [(Type, &str); 6] = [
+            (parse_quote!(u16), "u16"),
+            (parse_quote!(Vec<String>), "Vec<String>"),
+            (
+                parse_quote!(HashMap<String, Vec<u8>>),
+                "HashMap<String, Vec<u8>>",
+            ),
+            (parse_quote!(std::path::PathBuf), "std::path::PathBuf"),
+            (parse_quote!(Option<&'static str>), "Option<&'static str>"),
+            (parse_quote!([u8; 4]), "[u8; 4]"),
+        ];
+        for (ty, name) in names {
+            assert_eq!(compact(&ty), name);
+        }
+    }
+
+    #[test]
+    fn expressions_read_like_source() {
+        let range: Expr = parse_quote!(1..=65535);
+        assert_eq!(compact(&range), "1..=65535");
+        let options: Expr = parse_quote!(["debug", "info"]);
+        assert_eq!(compact(&options), r#"["debug", "info"]"#);
+        let call: Expr = parse_quote!(Duration::from_secs(30));
+        assert_eq!(compact(&call), "Duration::from_secs(30)");
+        let text: Expr = parse_quote!(String::from("a = \"b\" + c"));
+        assert_eq!(compact(&text), r#"String::from("a = \"b\" + c")"#);
+    }
+}
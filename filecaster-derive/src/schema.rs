@@ -0,0 +1,108 @@
+use crate::from_file::with_bounds;
+use crate::shadow::FieldDefault;
+use crate::validate::Validator;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{Error, FieldsNamed, Generics, Ident, LitStr, Result};
+
+/// What the schema of one field is built from.
+pub struct FieldSchema<'a> {
+    pub doc: Option<&'a str>,
+    pub default: &'a FieldDefault,
+    pub required: bool,
+    pub validators: &'a [Validator],
+    pub aliases: &'a [LitStr],
+    pub deprecated: bool,
+    pub secret: bool,
+}
+
+/// Generate the `filecaster::schema::JsonSchema` impl describing the shadow:
+/// every field with its doc comment, default and constraints, recursing
+/// through nested types. Aliases are listed as deprecated properties, and
+/// secrets have no default. The impl only exists if every field type has a
+/// schema.
+pub fn impl_json_schema(
+    name: &Ident,
+    generics: &Generics,
+    fields: &FieldsNamed,
+    schemas: &[FieldSchema],
+    doc: Option<&str>,
+    deny_unknown_keys: bool,
+) -> Result<TokenStream> {
+    let generics = with_bounds(
+        generics,
+        fields.named.iter().flat_map(|field| {
+            let ty = &field.ty;
+            [
+                quote! { #ty: filecaster::schema::JsonSchema + filecaster::ToShadow },
+                quote! { <#ty as filecaster::FromFile>::Shadow: serde::Serialize },
+            ]
+        }),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut required = Vec::new();
+    let properties = fields
+        .named
+        .iter()
+        .zip(schemas)
+        .map(|(field, schema)| {
+            let ident = field
+                .ident
+                .as_ref()
+                .ok_or_else(|| Error::new_spanned(field, "Expected named fields"))?;
+            let ty = &field.ty;
+            let key = ident.unraw().to_string();
+            if schema.required {
+                required.push(key.clone());
+            }
+            let description = option(schema.doc);
+            let default = if schema.secret {
+                quote! { None::<#ty> }
+            } else {
+                schema.default.value(ty)
+            };
+            let secret = schema
+                .secret
+                .then(|| quote! { __schema = filecaster::schema::secret(__schema); });
+            let constraints = schema.validators.iter().filter_map(Validator::schema);
+            let aliases = schema.aliases;
+            let deprecated = (schema.deprecated && aliases.is_empty())
+                .then(|| quote! { __schema = filecaster::schema::deprecated(__schema); });
+            Ok(quote! {
+                let mut __schema = filecaster::schema::property::<#ty>(#description, #default);
+                #(#constraints)*
+                #secret
+                #deprecated
+                #(
+                    __properties.push((#aliases, filecaster::schema::deprecated(__schema.clone())));
+                )*
+                __properties.push((#key, __schema));
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let description = option(doc);
+
+    Ok(quote! {
+        impl #impl_generics filecaster::schema::JsonSchema for #name #ty_generics #where_clause {
+            fn json_schema() -> filecaster::schema::Schema {
+                let mut __properties = Vec::new();
+                #(#properties)*
+                filecaster::schema::object(
+                    #description,
+                    #deny_unknown_keys,
+                    __properties,
+                    &[#(#required),*],
+                )
+            }
+        }
+    })
+}
+
+fn option(text: Option<&str>) -> TokenStream {
+    match text {
+        Some(text) => quote! { Some(#text) },
+        None => quote! { None },
+    }
+}
@@ -0,0 +1,153 @@
+use crate::from_file::with_bounds;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Error, Expr, FieldsNamed, Generics, Ident, Result, Type};
+
+/// What a field resolves to when its shadow is unset, if anything.
+pub enum FieldDefault {
+    Expr(Expr),
+    FromFile,
+    Required,
+}
+
+impl FieldDefault {
+    /// Expression evaluating to the default of a field of type `ty`, as an
+    /// `Option<ty>`.
+    pub fn value(&self, ty: &Type) -> TokenStream {
+        match self {
+            Self::Expr(expr) => quote! { Some(#expr) },
+            Self::FromFile => quote! { <#ty as filecaster::FromFile>::try_from_file(None).ok() },
+            Self::Required => quote! { None::<#ty> },
+        }
+    }
+}
+
+/// Generate the `ToShadow` impl and `From<&T>` for the shadow, setting every
+/// field and recursing through nested types; `defaults` holds the
+/// [`FieldDefault`] of each field and `secrets` whether it is left out of
+/// the redacted shadow. The impls only exist if every field type implements
+/// `ToShadow`; the `MinimalShadow` impl, comparing fields with their
+/// defaults, also needs `MinimalShadow` and `PartialEq`.
+pub fn impl_to_shadow(
+    name: &Ident,
+    file_ident: &Ident,
+    generics: &Generics,
+    fields: &FieldsNamed,
+    defaults: &[FieldDefault],
+    secrets: &[bool],
+) -> Result<TokenStream> {
+    let minimal_generics = with_bounds(
+        generics,
+        fields.named.iter().map(|field| {
+            let ty = &field.ty;
+            quote! { #ty: filecaster::MinimalShadow + PartialEq }
+        }),
+    );
+    let generics = with_bounds(
+        generics,
+        fields.named.iter().map(|field| {
+            let ty = &field.ty;
+            quote! { #ty: filecaster::ToShadow }
+        }),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (minimal_impl_generics, _, minimal_where_clause) = minimal_generics.split_for_impl();
+
+    let field_shadows = fields
+        .named
+        .iter()
+        .map(|field| {
+            let ident = field
+                .ident
+                .as_ref()
+                .ok_or_else(|| Error::new_spanned(field, "Expected named fields"))?;
+            let ty = &field.ty;
+            Ok(quote! {
+                #ident: Some(<#ty as filecaster::ToShadow>::to_shadow(&self.#ident))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let minimal_shadows = fields
+        .named
+        .iter()
+        .zip(defaults)
+        .filter_map(|(field, default)| Some((field.ident.as_ref()?, &field.ty, default)))
+        .map(|(ident, ty, default)| {
+            let default = default.value(ty);
+            quote! {
+                #ident: match #default {
+                    Some(default) if self.#ident == default => None,
+                    _ => Some(<#ty as filecaster::MinimalShadow>::to_minimal_shadow(&self.#ident)),
+                }
+            }
+        });
+
+    let default_shadows = fields
+        .named
+        .iter()
+        .zip(defaults)
+        .filter_map(|(field, default)| Some((field.ident.as_ref()?, &field.ty, default)))
+        .map(|(ident, ty, default)| match default {
+            FieldDefault::FromFile => quote! {
+                #ident: <#ty as filecaster::ToShadow>::default_shadow()
+            },
+            default => {
+                let default = default.value(ty);
+                quote! {
+                    #ident: #default.as_ref().map(<#ty as filecaster::ToShadow>::to_shadow)
+                }
+            }
+        });
+
+    let redacted_shadows = fields
+        .named
+        .iter()
+        .zip(secrets)
+        .filter_map(|(field, &secret)| Some((field.ident.as_ref()?, &field.ty, secret)))
+        .map(|(ident, ty, secret)| {
+            if secret {
+                quote! { #ident: None }
+            } else {
+                quote! {
+                    #ident: Some(<#ty as filecaster::ToShadow>::to_redacted_shadow(&self.#ident))
+                }
+            }
+        });
+
+    Ok(quote! {
+        impl #impl_generics filecaster::ToShadow for #name #ty_generics #where_clause {
+            fn to_shadow(&self) -> #file_ident #ty_generics {
+                #file_ident {
+                    #(#field_shadows),*
+                }
+            }
+
+            fn default_shadow() -> Option<#file_ident #ty_generics> {
+                Some(#file_ident {
+                    #(#default_shadows),*
+                })
+            }
+
+            fn to_redacted_shadow(&self) -> #file_ident #ty_generics {
+                #file_ident {
+                    #(#redacted_shadows),*
+                }
+            }
+        }
+
+        impl #impl_generics From<&#name #ty_generics> for #file_ident #ty_generics #where_clause {
+            fn from(value: &#name #ty_generics) -> Self {
+                <#name #ty_generics as filecaster::ToShadow>::to_shadow(value)
+            }
+        }
+
+        impl #minimal_impl_generics filecaster::MinimalShadow for #name #ty_generics #minimal_where_clause {
+            fn to_minimal_shadow(&self) -> #file_ident #ty_generics {
+                #file_ident {
+                    #(#minimal_shadows),*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,313 @@
+use crate::meta::compact;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{BinOp, Error, Expr, Ident, LitStr, PathArguments, Result, Token, Type, parenthesized};
+
+/// One validation option of a field's `#[from_file(...)]` attribute.
+#[derive(Debug)]
+pub enum Validator {
+    Range(Expr),
+    MinLen(Expr),
+    MaxLen(Expr),
+    NonEmpty,
+    OneOf(Expr),
+    Regex(LitStr),
+    Custom(Expr),
+}
+
+impl Validator {
+    /// Parses `meta` if it is a validation option, returning `None` otherwise.
+    pub fn parse(meta: &ParseNestedMeta) -> Result<Option<Self>> {
+        let path = &meta.path;
+        let validator = if path.is_ident("range") {
+            Self::Range(meta.value()?.parse()?)
+        } else if path.is_ident("min_len") {
+            Self::MinLen(meta.value()?.parse()?)
+        } else if path.is_ident("max_len") {
+            Self::MaxLen(meta.value()?.parse()?)
+        } else if path.is_ident("non_empty") {
+            Self::NonEmpty
+        } else if path.is_ident("one_of") {
+            Self::OneOf(meta.value()?.parse()?)
+        } else if path.is_ident("regex") {
+            let pattern: LitStr = meta.value()?.parse()?;
+            check_regex(&pattern)?;
+            Self::Regex(pattern)
+        } else if path.is_ident("validate") {
+            Self::Custom(meta.value()?.parse()?)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(validator))
+    }
+
+    /// Expression checking `__value`, evaluating to `Result<(), filecaster::Error>`.
+    /// The value of a `secret` field is masked in the error message.
+    fn check(&self, secret: bool) -> TokenStream {
+        let shown = if secret {
+            quote! { filecaster::validate::redacted }
+        } else {
+            quote! { filecaster::validate }
+        };
+        match self {
+            Self::Range(range) => quote! { #shown::range(__value, #range) },
+            Self::MinLen(min) => quote! { filecaster::validate::min_len(__value, #min) },
+            Self::MaxLen(max) => quote! { filecaster::validate::max_len(__value, #max) },
+            Self::NonEmpty => quote! { filecaster::validate::non_empty(__value) },
+            Self::OneOf(options) => quote! { #shown::one_of(__value, &#options) },
+            Self::Regex(pattern) => quote! {{
+                static __REGEX: std::sync::OnceLock<filecaster::validate::Regex> =
+                    std::sync::OnceLock::new();
+                #shown::regex(
+                    __value,
+                    filecaster::validate::compiled(&__REGEX, #pattern),
+                )
+            }},
+            Self::Custom(check) => quote! {
+                (#check)(__value).map_err(filecaster::Error::invalid)
+            },
+        }
+    }
+
+    /// The option as written in the attribute, e.g. `range = 1..=65535`.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Range(range) => format!("range = {}", compact(range)),
+            Self::MinLen(min) => format!("min_len = {}", compact(min)),
+            Self::MaxLen(max) => format!("max_len = {}", compact(max)),
+            Self::NonEmpty => "non_empty".to_string(),
+            Self::OneOf(options) => format!("one_of = {}", compact(options)),
+            Self::Regex(pattern) => format!("regex = {}", compact(pattern)),
+            Self::Custom(check) => format!("validate = {}", compact(check)),
+        }
+    }
+
+    /// Statement adding the constraint to the field's `__schema`, if JSON
+    /// Schema can express it.
+    pub fn schema(&self) -> Option<TokenStream> {
+        let call = match self {
+            Self::Range(range) => quote! { range(&mut __schema, #range) },
+            Self::MinLen(min) => quote! { min_len(&mut __schema, #min) },
+            Self::MaxLen(max) => quote! { max_len(&mut __schema, #max) },
+            Self::NonEmpty => quote! { min_len(&mut __schema, 1) },
+            Self::OneOf(options) => quote! { one_of(&mut __schema, &#options) },
+            Self::Regex(pattern) => quote! { pattern(&mut __schema, #pattern) },
+            Self::Custom(_) => return None,
+        };
+        Some(quote! { filecaster::schema::#call; })
+    }
+}
+
+/// Rejects a `regex = "..."` pattern that does not compile, so that a typo
+/// fails the build rather than every load.
+#[cfg(feature = "regex")]
+fn check_regex(pattern: &LitStr) -> Result<()> {
+    regex::Regex::new(&pattern.value())
+        .map(drop)
+        .map_err(|e| Error::new_spanned(pattern, format!("invalid regex: {e}")))
+}
+
+#[cfg(not(feature = "regex"))]
+fn check_regex(pattern: &LitStr) -> Result<()> {
+    Err(Error::new_spanned(
+        pattern,
+        "`regex` requires the `regex` feature of filecaster",
+    ))
+}
+
+/// Runs every validator on the resolved value of a field of type `ty`,
+/// collecting failures under the field's `key`. For an `Option<T>` field,
+/// the built-in checks only see the `Some` value, while `validate` gets the
+/// whole `Option`.
+pub fn field_checks(
+    key: &str,
+    local: &Ident,
+    ty: &Type,
+    validators: &[Validator],
+    secret: bool,
+) -> TokenStream {
+    if validators.is_empty() {
+        return quote! {};
+    }
+    let run = |validators: &[&Validator]| {
+        let checks = validators.iter().map(|validator| validator.check(secret));
+        quote! {
+            #(
+                filecaster::collect_error(#checks.map_err(|e| e.at(#key)), &mut __errors);
+            )*
+        }
+    };
+    let (custom, builtin): (Vec<_>, Vec<_>) = validators
+        .iter()
+        .partition(|validator| matches!(validator, Validator::Custom(_)));
+    let checks = if is_option(ty) && !builtin.is_empty() {
+        let custom = run(&custom);
+        let builtin = run(&builtin);
+        quote! {
+            #custom
+            if let Some(__value) = __value {
+                #builtin
+            }
+        }
+    } else {
+        run(&validators.iter().collect::<Vec<_>>())
+    };
+    quote! {
+        if let Some(__value) = &#local {
+            #checks
+        }
+    }
+}
+
+/// Whether `ty` is written as `Option<T>`.
+fn is_option(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.qself.is_none()
+        && path.path.segments.last().is_some_and(|segment| {
+            segment.ident == "Option"
+                && matches!(segment.arguments, PathArguments::AngleBracketed(_))
+        })
+}
+
+/// A dotted path to a field, relative to the struct, such as `tls.cert_path`.
+#[derive(Debug)]
+pub struct FieldPath(Vec<Ident>);
+
+impl FieldPath {
+    fn key(&self) -> String {
+        self.0
+            .iter()
+            .map(|ident| ident.unraw().to_string())
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Expression borrowing the field from `__value`.
+    fn access(&self) -> TokenStream {
+        let idents = &self.0;
+        quote! { &__value.#(#idents).* }
+    }
+
+    /// `(key, is_present)` pair for `exactly_one_of` and `requires`.
+    fn presence(&self) -> TokenStream {
+        let key = self.key();
+        let access = self.access();
+        quote! { (#key, filecaster::validate::Presence::is_present(#access)) }
+    }
+}
+
+impl Parse for FieldPath {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let idents =
+            Punctuated::<Ident, Token![.]>::parse_separated_nonempty_with(input, Ident::parse_any)?;
+        Ok(Self(idents.into_iter().collect()))
+    }
+}
+
+/// One struct-level constraint of a `#[from_file(...)]` container attribute.
+#[derive(Debug)]
+pub enum ContainerCheck {
+    Custom(Expr),
+    ExactlyOneOf(Vec<FieldPath>),
+    Requires(FieldPath, Vec<FieldPath>),
+    Compare(FieldPath, Ident, FieldPath),
+}
+
+impl ContainerCheck {
+    /// Parses `meta` if it is a struct-level constraint, returning `None` otherwise.
+    pub fn parse(meta: &ParseNestedMeta) -> Result<Option<Self>> {
+        let path = &meta.path;
+        let check = if path.is_ident("validate") {
+            Self::Custom(meta.value()?.parse()?)
+        } else if path.is_ident("exactly_one_of") {
+            let paths = parse_paths(meta)?;
+            if paths.len() < 2 {
+                return Err(meta.error("`exactly_one_of` needs at least two fields"));
+            }
+            Self::ExactlyOneOf(paths)
+        } else if path.is_ident("requires") {
+            let mut paths = parse_paths(meta)?.into_iter();
+            let field = paths
+                .next()
+                .ok_or_else(|| meta.error("`requires` needs a field"))?;
+            let required = paths.collect::<Vec<_>>();
+            if required.is_empty() {
+                return Err(meta.error("`requires` needs at least one required field"));
+            }
+            Self::Requires(field, required)
+        } else if path.is_ident("check") {
+            let content;
+            parenthesized!(content in meta.input);
+            let lhs = content.parse()?;
+            let op = content.parse::<BinOp>()?;
+            let variant = match op {
+                BinOp::Lt(_) => "Lt",
+                BinOp::Le(_) => "Le",
+                BinOp::Gt(_) => "Gt",
+                BinOp::Ge(_) => "Ge",
+                BinOp::Eq(_) => "Eq",
+                BinOp::Ne(_) => "Ne",
+                _ => return Err(Error::new_spanned(op, "expected a comparison operator")),
+            };
+            let rhs = content.parse()?;
+            Self::Compare(lhs, Ident::new(variant, Span::call_site()), rhs)
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(check))
+    }
+
+    /// Expression checking the resolved struct `__value`, evaluating to
+    /// `Result<(), filecaster::Error>`.
+    fn check(&self) -> TokenStream {
+        match self {
+            Self::Custom(check) => quote! {
+                (#check)(&__value).map_err(filecaster::Error::invalid)
+            },
+            Self::ExactlyOneOf(fields) => {
+                let fields = fields.iter().map(FieldPath::presence);
+                quote! { filecaster::validate::exactly_one_of(&[#(#fields),*]) }
+            }
+            Self::Requires(field, required) => {
+                let field = field.presence();
+                let required = required.iter().map(FieldPath::presence);
+                quote! { filecaster::validate::requires(#field, &[#(#required),*]) }
+            }
+            Self::Compare(lhs, op, rhs) => {
+                let (lhs_key, lhs) = (lhs.key(), lhs.access());
+                let (rhs_key, rhs) = (rhs.key(), rhs.access());
+                quote! {
+                    filecaster::validate::redacted::compare::<Self, _>(
+                        (#lhs_key, #lhs),
+                        filecaster::validate::Comparison::#op,
+                        (#rhs_key, #rhs),
+                    )
+                }
+            }
+        }
+    }
+}
+
+fn parse_paths(meta: &ParseNestedMeta) -> Result<Vec<FieldPath>> {
+    let content;
+    parenthesized!(content in meta.input);
+    let paths = Punctuated::<FieldPath, Token![,]>::parse_terminated(&content)?;
+    Ok(paths.into_iter().collect())
+}
+
+/// Runs every struct-level constraint on the resolved struct `__value`,
+/// collecting failures without a path of their own.
+pub fn container_checks(checks: &[ContainerCheck]) -> TokenStream {
+    let checks = checks.iter().map(ContainerCheck::check);
+    quote! {
+        #(
+            filecaster::collect_error(#checks, &mut __errors);
+        )*
+    }
+}
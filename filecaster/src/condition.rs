@@ -0,0 +1,370 @@
+//! `[when.'<predicate>']` sections that only apply on matching hosts.
+//!
+//! A predicate compares a subject with a quoted pattern, where `*` matches
+//! any run of characters and `?` a single one:
+//!
+//! - `hostname = "web-*"`
+//! - `target_os = "linux"`
+//! - `env.REGION = "eu"`
+//! - `profile = "prod"`
+//!
+//! Predicates combine with `all(...)`, `any(...)` and `not(...)`, and may be
+//! wrapped in `cfg(...)` for readability: `cfg(all(target_os = "linux", not(profile = "dev")))`.
+
+use crate::value::Value;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Key of the table holding the conditional sections.
+pub const WHEN_KEY: &str = "when";
+
+/// The runtime facts predicates are evaluated against.
+#[derive(Debug, Clone, Default)]
+pub struct Context {
+    pub hostname: Option<String>,
+    pub profile: Option<String>,
+    /// Environment variables, or `None` to read the process environment.
+    pub vars: Option<HashMap<String, String>>,
+}
+
+impl Context {
+    fn var(&self, name: &str) -> Option<String> {
+        match &self.vars {
+            Some(vars) => vars.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        }
+    }
+}
+
+/// Merges every matching `when` section over the rest of the document.
+///
+/// Sections are applied in the order of their keys. A `when` key that is not
+/// a table is an ordinary value and left alone.
+pub fn apply(document: &mut Value, context: &Context) -> Result<(), String> {
+    let Value::Map(map) = document else {
+        return Ok(());
+    };
+    if !matches!(map.get(WHEN_KEY), Some(Value::Map(_))) {
+        return Ok(());
+    }
+    let Some(Value::Map(sections)) = map.remove(WHEN_KEY) else {
+        return Ok(());
+    };
+    for (condition, section) in sections {
+        let predicate = Parser::new(&condition)
+            .parse()
+            .map_err(|e| format!("invalid condition `{condition}`: {e}"))?;
+        if predicate.matches(context) {
+            document.merge(section);
+        }
+    }
+    Ok(())
+}
+
+/// Best-effort lookup of the machine's host name.
+pub fn hostname() -> Option<String> {
+    ["/proc/sys/kernel/hostname", "/etc/hostname"]
+        .into_iter()
+        .find_map(|path| std::fs::read_to_string(Path::new(path)).ok())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[derive(Debug, PartialEq)]
+enum Subject {
+    Hostname,
+    TargetOs,
+    Profile,
+    Env(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Predicate {
+    Matches { subject: Subject, pattern: String },
+    All(Vec<Self>),
+    Any(Vec<Self>),
+    Not(Box<Self>),
+}
+
+impl Predicate {
+    fn matches(&self, context: &Context) -> bool {
+        match self {
+            Self::Matches { subject, pattern } => {
+                let actual = match subject {
+                    Subject::Hostname => context.hostname.clone(),
+                    Subject::TargetOs => Some(std::env::consts::OS.to_string()),
+                    Subject::Profile => context.profile.clone(),
+                    Subject::Env(var) => context.var(var),
+                };
+                actual.is_some_and(|actual| glob_match(pattern, &actual))
+            }
+            Self::All(all) => all.iter().all(|p| p.matches(context)),
+            Self::Any(any) => any.iter().any(|p| p.matches(context)),
+            Self::Not(inner) => !inner.matches(context),
+        }
+    }
+}
+
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    const fn new(input: &'a str) -> Self {
+        Self { rest: input }
+    }
+
+    fn parse(mut self) -> Result<Predicate, String> {
+        let predicate = self.predicate()?;
+        self.skip_whitespace();
+        if self.rest.is_empty() {
+            Ok(predicate)
+        } else {
+            Err(format!("unexpected `{}`", self.rest))
+        }
+    }
+
+    fn predicate(&mut self) -> Result<Predicate, String> {
+        let name = self.ident()?;
+        if self.eat('(') {
+            let mut args = vec![self.predicate()?];
+            while self.eat(',') {
+                args.push(self.predicate()?);
+            }
+            self.expect(')')?;
+            return match (name, args.len()) {
+                ("all", _) => Ok(Predicate::All(args)),
+                ("any", _) => Ok(Predicate::Any(args)),
+                ("not", 1) => Ok(Predicate::Not(Box::new(args.remove(0)))),
+                ("cfg", 1) => Ok(args.remove(0)),
+                ("not" | "cfg", _) => Err(format!("`{name}(...)` takes exactly one predicate")),
+                _ => Err(format!("unknown combinator `{name}`")),
+            };
+        }
+        let subject = match name {
+            "hostname" => Subject::Hostname,
+            "target_os" => Subject::TargetOs,
+            "profile" => Subject::Profile,
+            _ => match name.strip_prefix("env.") {
+                Some(var) if !var.is_empty() => Subject::Env(var.to_string()),
+                _ => return Err(format!("unknown subject `{name}`")),
+            },
+        };
+        self.expect('=')?;
+        let pattern = self.string()?;
+        Ok(Predicate::Matches { subject, pattern })
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_whitespace();
+        self.rest
+            .strip_prefix(c)
+            .map(|rest| self.rest = rest)
+            .is_some()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected `{c}`"))
+        }
+    }
+
+    fn ident(&mut self) -> Result<&'a str, String> {
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(self.rest.len());
+        if end == 0 {
+            return Err("expected a name".to_string());
+        }
+        let (ident, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Ok(ident)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut value = String::new();
+        let mut chars = self.rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.rest = &self.rest[i + 1..];
+                    return Ok(value);
+                }
+                '\\' => value.extend(chars.next().map(|(_, c)| c)),
+                c => value.push(c),
+            }
+        }
+        Err("unterminated string".to_string())
+    }
+}
+
+/// Matches `text` against a pattern where `*` matches any run of characters
+/// and `?` exactly one.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(&c) if c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::tests::map;
+
+    fn parse(input: &str) -> Result<Predicate, String> {
+        Parser::new(input).parse()
+    }
+
+    fn context() -> Context {
+        Context {
+            hostname: Some("web-03".to_string()),
+            profile: Some("prod".to_string()),
+            vars: Some(HashMap::from([(
+                "REGION".to_string(),
+                "eu-west".to_string(),
+            )])),
+        }
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob_match("web-*", "web-03"));
+        assert!(glob_match("web-??", "web-03"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*-03", "web-03"));
+        assert!(glob_match("w*b*3", "web-03"));
+        assert!(!glob_match("web-?", "web-03"));
+        assert!(!glob_match("db-*", "web-03"));
+        assert!(!glob_match("web", "web-03"));
+    }
+
+    #[test]
+    fn parse_simple_predicates() {
+        assert_eq!(
+            parse(r#"hostname = "web-*""#).unwrap(),
+            Predicate::Matches {
+                subject: Subject::Hostname,
+                pattern: "web-*".to_string(),
+            }
+        );
+        assert_eq!(
+            parse(r#"env.REGION="eu""#).unwrap(),
+            Predicate::Matches {
+                subject: Subject::Env("REGION".to_string()),
+                pattern: "eu".to_string(),
+            }
+        );
+        assert_eq!(
+            parse(r#"cfg(target_os = "linux")"#).unwrap(),
+            Predicate::Matches {
+                subject: Subject::TargetOs,
+                pattern: "linux".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("").is_err());
+        assert!(parse("hostname").is_err());
+        assert!(parse(r#"hostname = "web"#).is_err());
+        assert!(parse(r#"colour = "red""#).is_err());
+        assert!(parse(r#"not(profile = "a", profile = "b")"#).is_err());
+        assert!(parse(r#"profile = "a" trailing"#).is_err());
+    }
+
+    #[test]
+    fn combinators() {
+        let ctx = context();
+        let matches = |input: &str| parse(input).unwrap().matches(&ctx);
+        assert!(matches(r#"all(hostname = "web-*", profile = "prod")"#));
+        assert!(!matches(r#"all(hostname = "web-*", profile = "dev")"#));
+        assert!(matches(r#"any(hostname = "db-*", profile = "prod")"#));
+        assert!(matches(r#"not(profile = "dev")"#));
+        assert!(matches(&format!(
+            r#"target_os = "{}""#,
+            std::env::consts::OS
+        )));
+    }
+
+    #[test]
+    fn missing_facts_never_match() {
+        let ctx = Context::default();
+        let predicate = parse(r#"hostname = "*""#).unwrap();
+        assert!(!predicate.matches(&ctx));
+    }
+
+    #[test]
+    fn apply_merges_matching_sections() {
+        let mut doc = map([
+            ("port", Value::Integer(1)),
+            (
+                "when",
+                map([
+                    (r#"hostname = "web-*""#, map([("port", Value::Integer(2))])),
+                    (r#"hostname = "db-*""#, map([("port", Value::Integer(3))])),
+                ]),
+            ),
+        ]);
+        apply(&mut doc, &context()).unwrap();
+        assert_eq!(doc, map([("port", Value::Integer(2))]));
+    }
+
+    #[test]
+    fn env_predicates_read_context_vars() {
+        let ctx = context();
+        let matches = |input: &str| parse(input).unwrap().matches(&ctx);
+        assert!(matches(r#"env.REGION = "eu-*""#));
+        assert!(!matches(r#"env.ZONE = "*""#));
+    }
+
+    #[test]
+    fn non_table_when_key_is_kept() {
+        let mut doc = map([("when", Value::String("always".into()))]);
+        apply(&mut doc, &context()).unwrap();
+        assert_eq!(doc, map([("when", Value::String("always".into()))]));
+    }
+
+    #[test]
+    fn apply_rejects_invalid_conditions() {
+        let mut doc = map([("when", map([("nonsense", map([]))]))]);
+        assert!(apply(&mut doc, &context()).is_err());
+    }
+}
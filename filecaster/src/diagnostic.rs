@@ -0,0 +1,350 @@
+//! Source locations attached to errors, rendered as annotated snippets.
+
+use std::fmt::{self, Display};
+#[cfg(feature = "serde")]
+use std::ops::Range;
+
+/// A position inside a configuration source, with the line it points into.
+///
+/// Its [`Display`] output is a compiler-style snippet:
+///
+/// ```text
+///  --> config.toml:3:8
+///   |
+/// 3 | port = "high"
+///   |        ^^^^^^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Location {
+    /// The file (or other source) the position refers to.
+    pub origin: String,
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// The full text of the line, without its line break.
+    pub snippet: String,
+    /// Number of characters highlighted from `column` on; zero, with an
+    /// empty `snippet`, if the line is not shown.
+    pub width: usize,
+}
+
+impl Location {
+    /// Resolves the byte range `span` of `text`, highlighting at most the
+    /// rest of its first line.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_span(origin: impl Into<String>, text: &str, span: Range<usize>) -> Self {
+        let start = span.start.min(text.len());
+        let before = text.get(..start).unwrap_or_default();
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let snippet = text[line_start..].lines().next().unwrap_or_default();
+        let width = text
+            .get(start..span.end.max(start))
+            .and_then(|s| s.lines().next())
+            .map_or(0, |s| s.chars().count());
+        Self {
+            origin: origin.into(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            snippet: snippet.to_string(),
+            width: width.max(1),
+        }
+    }
+
+    /// Points at a 1-based `line` and `column` of `text`, as reported by
+    /// parsers that do not expose byte offsets.
+    #[cfg(feature = "json")]
+    pub(crate) fn from_line_column(
+        origin: impl Into<String>,
+        text: &str,
+        line: usize,
+        column: usize,
+    ) -> Option<Self> {
+        let snippet = text.lines().nth(line.checked_sub(1)?)?;
+        Some(Self {
+            origin: origin.into(),
+            line,
+            column: column.max(1),
+            snippet: snippet.to_string(),
+            width: 1,
+        })
+    }
+}
+
+impl Location {
+    /// Drops the line of text, e.g. because it holds a secret; only the
+    /// position is then shown.
+    #[cfg(feature = "serde")]
+    pub(crate) fn without_snippet(mut self) -> Self {
+        self.snippet.clear();
+        self.width = 0;
+        self
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        // Keep tabs so the carets line up with the snippet above them.
+        let indent = self
+            .snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        write!(
+            f,
+            "{gutter}--> {}:{}:{}",
+            self.origin, self.line, self.column
+        )?;
+        if self.width == 0 {
+            return Ok(());
+        }
+        writeln!(f)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(f, "{gutter} | {indent}{}", "^".repeat(self.width))
+    }
+}
+
+/// One step of a field path such as `servers[1].port`.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Splits a dotted field path, with `[i]` list indices, into its segments.
+#[cfg(feature = "serde")]
+pub(crate) fn split_path(path: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    for part in path.split('.').filter(|part| !part.is_empty()) {
+        let (key, mut indices) = part.find('[').map_or((part, ""), |i| part.split_at(i));
+        if !key.is_empty() {
+            segments.push(Segment::Key(key));
+        }
+        while let Some(rest) = indices.strip_prefix('[') {
+            let Some((index, tail)) = rest.split_once(']') else {
+                break;
+            };
+            match index.parse() {
+                Ok(index) => segments.push(Segment::Index(index)),
+                Err(_) => segments.push(Segment::Key(index)),
+            }
+            indices = tail;
+        }
+    }
+    segments
+}
+
+/// Byte range of the value at `path` in a TOML document.
+#[cfg(feature = "toml")]
+pub(crate) fn toml_span(text: &str, path: &[Segment<'_>]) -> Option<Range<usize>> {
+    use toml::de::{DeTable, DeValue};
+
+    let root = DeTable::parse(text).ok()?;
+    let (first, rest) = path.split_first()?;
+    let Segment::Key(key) = first else {
+        return None;
+    };
+    let mut value = root.get_ref().get(*key)?;
+    for segment in rest {
+        value = match (value.get_ref(), segment) {
+            (DeValue::Table(table), Segment::Key(key)) => table.get(*key)?,
+            (DeValue::Array(array), Segment::Index(index)) => array.get(*index)?,
+            _ => return None,
+        };
+    }
+    Some(value.span())
+}
+
+/// Byte range of the value at `path` in a JSON document.
+#[cfg(feature = "json")]
+pub(crate) fn json_span(text: &str, path: &[Segment<'_>]) -> Option<Range<usize>> {
+    JsonScanner {
+        bytes: text.as_bytes(),
+        pos: 0,
+    }
+    .find(path)
+}
+
+/// A minimal JSON walker that only tracks byte offsets; the document has
+/// already been validated by the real parser.
+#[cfg(feature = "json")]
+struct JsonScanner<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+#[cfg(feature = "json")]
+impl<'a> JsonScanner<'a> {
+    fn find(&mut self, path: &[Segment<'_>]) -> Option<Range<usize>> {
+        self.skip_whitespace();
+        let Some((first, rest)) = path.split_first() else {
+            let start = self.pos;
+            self.skip_value()?;
+            return Some(start..self.pos);
+        };
+        match (self.bytes.get(self.pos)?, first) {
+            (b'{', Segment::Key(key)) => {
+                self.pos += 1;
+                loop {
+                    self.skip_whitespace();
+                    let name = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    if name == *key {
+                        return self.find(rest);
+                    }
+                    self.skip_value()?;
+                    self.skip_whitespace();
+                    self.expect(b',')?;
+                }
+            }
+            (b'[', Segment::Index(index)) => {
+                self.pos += 1;
+                for _ in 0..*index {
+                    self.skip_value()?;
+                    self.skip_whitespace();
+                    self.expect(b',')?;
+                }
+                self.skip_whitespace();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    return None;
+                }
+                self.find(rest)
+            }
+            _ => None,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        (self.bytes.get(self.pos) == Some(&byte)).then(|| self.pos += 1)
+    }
+
+    /// Consumes a string literal and returns its raw, still escaped, content.
+    fn string(&mut self) -> Option<&'a str> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        loop {
+            match self.bytes.get(self.pos)? {
+                b'\\' => self.pos += 2,
+                b'"' => break,
+                _ => self.pos += 1,
+            }
+        }
+        let content = std::str::from_utf8(&self.bytes[start..self.pos]).ok();
+        self.pos += 1;
+        content
+    }
+
+    fn skip_value(&mut self) -> Option<()> {
+        self.skip_whitespace();
+        match self.bytes.get(self.pos)? {
+            b'"' => self.string().map(drop),
+            b'{' | b'[' => {
+                let mut depth = 0_usize;
+                loop {
+                    match self.bytes.get(self.pos)? {
+                        b'"' => {
+                            self.string()?;
+                            continue;
+                        }
+                        b'{' | b'[' => depth += 1,
+                        b'}' | b']' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.pos += 1;
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.pos += 1;
+                }
+            }
+            _ => {
+                while self
+                    .bytes
+                    .get(self.pos)
+                    .is_some_and(|b| !matches!(b, b',' | b'}' | b']') && !b.is_ascii_whitespace())
+                {
+                    self.pos += 1;
+                }
+                Some(())
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_dotted_and_indexed_paths() {
+        assert_eq!(
+            split_path("servers[1].port"),
+            vec![
+                Segment::Key("servers"),
+                Segment::Index(1),
+                Segment::Key("port")
+            ]
+        );
+        assert_eq!(
+            split_path("matrix[0][2]"),
+            vec![Segment::Key("matrix"), Segment::Index(0), Segment::Index(2)]
+        );
+        assert!(split_path("").is_empty());
+    }
+
+    #[test]
+    fn location_from_span() {
+        let text = "name = \"app\"\nport = \"high\"\n";
+        let location = Location::from_span("config.toml", text, 20..26);
+        assert_eq!(location.line, 2);
+        assert_eq!(location.column, 8);
+        assert_eq!(location.width, 6);
+        assert_eq!(
+            location.to_string(),
+            " --> config.toml:2:8\n  |\n2 | port = \"high\"\n  |        ^^^^^^"
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_value_spans() {
+        let text = "[server]\nport = 80\nhosts = [\"a\", \"b\"]\n";
+        let span = toml_span(text, &split_path("server.port")).unwrap();
+        assert_eq!(&text[span], "80");
+        let span = toml_span(text, &split_path("server.hosts[1]")).unwrap();
+        assert_eq!(&text[span], "\"b\"");
+        assert!(toml_span(text, &split_path("server.missing")).is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_value_spans() {
+        let text =
+            r#"{ "a": { "skip": [1, {"x": "]"}], "port": 80 }, "list": [true, { "b": null }] }"#;
+        let span = json_span(text, &split_path("a.port")).unwrap();
+        assert_eq!(&text[span], "80");
+        let span = json_span(text, &split_path("list[1].b")).unwrap();
+        assert_eq!(&text[span], "null");
+        let span = json_span(text, &split_path("a.skip")).unwrap();
+        assert_eq!(&text[span], r#"[1, {"x": "]"}]"#);
+        assert!(json_span(text, &split_path("list[2]")).is_none());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::FromFile;
+use std::fmt::{self, Debug, Display};
+
+/// Rendering of a shadow field that is not set.
+pub const UNSET: &str = "<unset>";
+
+/// Rendering of the value of a `#[from_file(secret)]` field.
+pub const REDACTED: &str = "***";
+
+/// A single field that differs between two configurations.
+///
+/// `path` is the dotted path of the leaf field (e.g. `server.port`), while
+/// `old` and `new` hold the `Debug` rendering of the two values, or
+/// [`UNSET`] for a shadow field that is not set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldChange {
+    pub path: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl FieldChange {
+    /// Returns `true` if this change lies at or below the given section path.
+    ///
+    /// `"server"` matches `server`, `server.port` and `server.tls.cert`, but
+    /// not `server_name`.
+    #[must_use]
+    pub fn is_within(&self, section: &str) -> bool {
+        self.path
+            .strip_prefix(section)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    }
+}
+
+impl Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.path, self.old, self.new)
+    }
+}
+
+/// Field-by-field comparison of two resolved configurations, or of two
+/// shadows.
+///
+/// Implemented automatically by `#[derive(FromFile)]` for the struct and its
+/// shadow, recursing into nested `FromFile` structs, if every field type
+/// implements it. Leaf values (any `FromFile` type whose shadow is itself)
+/// are compared with `PartialEq` and rendered with `Debug`.
+pub trait Diff {
+    /// Appends every changed leaf below `path` to `changes`.
+    ///
+    /// An empty `path` denotes the root of the configuration.
+    fn diff_into(&self, new: &Self, path: &str, changes: &mut Vec<FieldChange>);
+
+    /// Like [`Diff::diff_into`], for values that may be unset, as the fields
+    /// of a shadow are.
+    fn diff_optional_into(
+        old: Option<&Self>,
+        new: Option<&Self>,
+        path: &str,
+        changes: &mut Vec<FieldChange>,
+    ) where
+        Self: Sized,
+    {
+        match (old, new) {
+            (Some(old), Some(new)) => old.diff_into(new, path, changes),
+            (None, None) => {}
+            (old, new) => changes.push(FieldChange {
+                path: path.to_string(),
+                old: old.map_or(UNSET, |_| "<set>").to_string(),
+                new: new.map_or(UNSET, |_| "<set>").to_string(),
+            }),
+        }
+    }
+
+    /// Returns every leaf field that differs between `old` and `new`.
+    fn diff(old: &Self, new: &Self) -> Vec<FieldChange> {
+        let mut changes = Vec::new();
+        old.diff_into(new, "", &mut changes);
+        changes
+    }
+}
+
+impl<T> Diff for T
+where
+    T: FromFile<Shadow = T> + PartialEq + Debug,
+{
+    fn diff_into(&self, new: &Self, path: &str, changes: &mut Vec<FieldChange>) {
+        if self != new {
+            changes.push(FieldChange {
+                path: path.to_string(),
+                old: format!("{self:?}"),
+                new: format!("{new:?}"),
+            });
+        }
+    }
+
+    fn diff_optional_into(
+        old: Option<&Self>,
+        new: Option<&Self>,
+        path: &str,
+        changes: &mut Vec<FieldChange>,
+    ) {
+        match (old, new) {
+            (Some(old), Some(new)) => old.diff_into(new, path, changes),
+            (None, None) => {}
+            (old, new) => changes.push(FieldChange {
+                path: path.to_string(),
+                old: old.map_or_else(|| UNSET.to_string(), |old| format!("{old:?}")),
+                new: new.map_or_else(|| UNSET.to_string(), |new| format!("{new:?}")),
+            }),
+        }
+    }
+}
+
+/// Every change between two configurations, or two shadows, ready to be
+/// shown to an operator.
+///
+/// Its [`Display`] output is a report with one line per changed field:
+///
+/// ```text
+/// 2 fields changed:
+///   name: "staging" -> "prod"
+///   server.port: 8080 -> 443
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct ConfigDiff {
+    pub changes: Vec<FieldChange>,
+}
+
+impl ConfigDiff {
+    /// Compares `old` and `new` field by field.
+    #[must_use]
+    pub fn new<T: Diff>(old: &T, new: &T) -> Self {
+        Self {
+            changes: T::diff(old, new),
+        }
+    }
+
+    /// Returns `true` if nothing changed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The changes as a JSON array of `{"path", "old", "new"}` objects.
+    #[cfg(feature = "json")]
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|_| "[]".to_string())
+    }
+}
+
+impl Display for ConfigDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.changes.len() {
+            0 => return f.write_str("no changes"),
+            1 => f.write_str("1 field changed:")?,
+            n => write!(f, "{n} fields changed:")?,
+        }
+        for change in &self.changes {
+            write!(f, "\n  {change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Masks the values of `changes`, found below a `#[from_file(secret)]`
+/// field, with [`REDACTED`]; unset values stay [`UNSET`].
+#[doc(hidden)]
+pub fn redact(changes: &mut [FieldChange]) {
+    for change in changes {
+        for value in [&mut change.old, &mut change.new] {
+            if value != UNSET {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+}
+
+/// Joins a parent path and a field name with a `.`.
+#[doc(hidden)]
+#[must_use]
+pub fn join_path(parent: &str, field: &str) -> String {
+    if parent.is_empty() {
+        field.to_string()
+    } else {
+        format!("{parent}.{field}")
+    }
+}
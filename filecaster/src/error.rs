@@ -0,0 +1,239 @@
+use crate::diagnostic::Location;
+use std::fmt::{self, Display};
+use std::io;
+use std::path::PathBuf;
+
+/// Errors produced while loading and resolving a configuration.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A configuration file could not be read.
+    Io { path: PathBuf, source: io::Error },
+    /// A configuration source is not valid in its format.
+    Parse {
+        origin: String,
+        message: String,
+        location: Option<Box<Location>>,
+    },
+    /// A value was present but could not be used for the field at `path`.
+    Field {
+        path: String,
+        message: String,
+        location: Option<Box<Location>>,
+    },
+    /// Fields marked `#[from_file(required)]` had no value, listed by path.
+    Missing { paths: Vec<String> },
+    /// A key that no field accepts, in a struct denying unknown keys.
+    UnknownKey {
+        path: String,
+        suggestion: Option<String>,
+        location: Option<Box<Location>>,
+    },
+    /// Several independent problems, in the order they were found.
+    Multiple { errors: Vec<Self> },
+}
+
+impl Error {
+    /// Combines `errors` into one error.
+    ///
+    /// Nested [`Error::Multiple`]s are flattened and every [`Error::Missing`]
+    /// is merged into the first one, dropping paths that already have a
+    /// [`Error::Field`] error. A single remaining error is returned as is.
+    #[must_use]
+    pub fn multiple(errors: Vec<Self>) -> Self {
+        let mut flat = Vec::new();
+        flatten(errors, &mut flat);
+        let invalid = flat
+            .iter()
+            .filter_map(|error| match error {
+                Self::Field { path, .. } => Some(path.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut combined = Vec::new();
+        let mut missing_at = None;
+        let mut missing = Vec::new();
+        for error in flat {
+            match error {
+                Self::Missing { paths } => {
+                    missing_at.get_or_insert(combined.len());
+                    missing.extend(paths.into_iter().filter(|path| !invalid.contains(path)));
+                }
+                error => combined.push(error),
+            }
+        }
+        if let Some(index) = missing_at
+            && !missing.is_empty()
+        {
+            combined.insert(index, Self::Missing { paths: missing });
+        }
+        if combined.len() == 1 {
+            combined.remove(0)
+        } else {
+            Self::Multiple { errors: combined }
+        }
+    }
+
+    /// Creates a [`Error::Field`] error for the value currently being built.
+    ///
+    /// The path starts out empty and is filled in by [`Error::at`] as the
+    /// error propagates through the enclosing structs.
+    pub fn invalid(message: impl Display) -> Self {
+        Self::Field {
+            path: String::new(),
+            message: message.to_string(),
+            location: None,
+        }
+    }
+
+    /// Prefixes the field path of this error with `segment`.
+    ///
+    /// Called by the derived `try_from_file` for every nested field, so a
+    /// failure deep inside a configuration reports e.g. `server.tls.port`.
+    #[must_use]
+    pub fn at(mut self, segment: &str) -> Self {
+        match &mut self {
+            Self::Field { path, .. } => *path = prefix_path(segment, path),
+            Self::Missing { paths } => {
+                for path in paths {
+                    *path = prefix_path(segment, path);
+                }
+            }
+            Self::UnknownKey { path, .. } => *path = prefix_path(segment, path),
+            Self::Multiple { errors } => {
+                *errors = std::mem::take(errors)
+                    .into_iter()
+                    .map(|error| error.at(segment))
+                    .collect();
+            }
+            Self::Io { .. } | Self::Parse { .. } => {}
+        }
+        self
+    }
+
+    /// Where in its source this error was detected, if known.
+    #[must_use]
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Self::Parse { location, .. }
+            | Self::Field { location, .. }
+            | Self::UnknownKey { location, .. } => location.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Attaches `found` to a parse or field error that has no location yet.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub(crate) fn with_location(mut self, found: Location) -> Self {
+        if let Self::Parse { location, .. }
+        | Self::Field { location, .. }
+        | Self::UnknownKey { location, .. } = &mut self
+        {
+            location.get_or_insert_with(|| Box::new(found));
+        }
+        self
+    }
+
+    /// Names the environment variable that set the value of a field error.
+    #[cfg(feature = "serde")]
+    #[must_use]
+    pub(crate) fn with_variable(mut self, var: &str) -> Self {
+        if let Self::Field { message, .. } = &mut self {
+            message.push_str(&format!(" (from environment variable `{var}`)"));
+        }
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => {
+                write!(f, "failed to read `{}`: {source}", path.display())
+            }
+            Self::Parse {
+                origin, message, ..
+            } => write!(f, "failed to parse `{origin}`: {message}"),
+            Self::Field { path, message, .. } if path.is_empty() => {
+                write!(f, "invalid value: {message}")
+            }
+            Self::Field { path, message, .. } => {
+                write!(f, "invalid value for `{path}`: {message}")
+            }
+            Self::Missing { paths } => {
+                write!(f, "missing required fields: ")?;
+                for (i, path) in paths.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { ", " };
+                    write!(f, "{sep}`{path}`")?;
+                }
+                Ok(())
+            }
+            Self::UnknownKey {
+                path, suggestion, ..
+            } => write_unknown_key(f, path, suggestion.as_deref()),
+            Self::Multiple { errors } => {
+                write!(f, "{} configuration errors:", errors.len())?;
+                for error in errors {
+                    // Indent continuation lines (snippets) under their bullet.
+                    let text = error.to_string().replace('\n', "\n  ");
+                    write!(f, "\n- {text}")?;
+                }
+                Ok(())
+            }
+        }?;
+        match self.location() {
+            Some(location) => write!(f, "\n{location}"),
+            None => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Prepends `segment` to a dotted field path; list indices (`[0]`) attach
+/// without a separating dot.
+pub(crate) fn prefix_path(segment: &str, path: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else if path.starts_with('[') {
+        format!("{segment}{path}")
+    } else {
+        format!("{segment}.{path}")
+    }
+}
+
+/// Stores the error of a field in `errors`, so that a derived
+/// `try_from_file` can report every failing field at once.
+#[doc(hidden)]
+pub fn collect_error<T>(result: Result<T, Error>, errors: &mut Vec<Error>) -> Option<T> {
+    result.map_err(|error| errors.push(error)).ok()
+}
+
+fn flatten(errors: Vec<Error>, into: &mut Vec<Error>) {
+    for error in errors {
+        match error {
+            Error::Multiple { errors } => flatten(errors, into),
+            error => into.push(error),
+        }
+    }
+}
+
+pub(crate) fn write_unknown_key(
+    f: &mut fmt::Formatter<'_>,
+    path: &str,
+    suggestion: Option<&str>,
+) -> fmt::Result {
+    write!(f, "unknown key `{path}`")?;
+    match suggestion {
+        Some(suggestion) => write!(f, ", did you mean `{suggestion}`?"),
+        None => Ok(()),
+    }
+}
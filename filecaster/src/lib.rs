@@ -25,6 +25,19 @@
 //! constructs your final `YourStruct`, applying default values for any fields
 //! that were `None` in the `YourStructFile`.
 //!
+//! The reverse direction is covered by [`ToShadow`]: `YourStructFile::from(&config)`
+//! sets every field, recursively, so a resolved configuration can be edited,
+//! serialized and layered again. [`MinimalShadow::to_minimal_shadow`] only sets
+//! the fields that differ from their defaults; unset fields are skipped when
+//! a shadow is serialized, so saved files stay small.
+//! [`ToShadow::default_shadow`] holds the defaults, field by field, as
+//! templates show them.
+//!
+//! With the `serde` feature, shadows implement [`PathAccess`]:
+//! `shadow.set_path("server.tls.port", "9443")` creates the `server` and
+//! `tls` sections as needed and parses the string into the field's type, and
+//! `shadow.get_path("server.tls.port")` reads it back as a [`Value`].
+//!
 //! ## Example
 //!
 //! While the `FromFile` trait is implemented via the `filecaster-derive` macro,
@@ -89,15 +102,196 @@
 //! }
 //! ```
 //!
+//! ## Fallible construction
+//!
+//! [`FromFile::try_from_file`] is the fallible counterpart of `from_file`. It
+//! returns an [`Error`] carrying the dotted path of the field that failed,
+//! e.g. `server.port`. [`Loader`] always resolves through `try_from_file`.
+//!
+//! Errors are not reported one at a time: derived implementations check
+//! every field, and [`Loader`] carries on past broken files, mistyped values
+//! and denied keys, returning everything it found as one
+//! [`Error::Multiple`], each entry tagged with its path and source.
+//!
+//! ## Diagnostics
+//!
+//! Syntax errors, type mismatches and values rejected by `try_from_file`
+//! carry a [`Location`] when they come from a file: its name, line, column
+//! and the offending line, which the error's `Display` renders as an
+//! annotated snippet. For field errors, [`Loader`] points at the last layer
+//! that set the value, including its `[profile.<name>]` sections.
+//!
+//! ## Unknown keys
+//!
+//! Misspelled keys are ignored by default. [`Loader::unknown_keys`] and the
+//! `#[from_file(unknown_keys = "...")]` container attribute turn them into
+//! [`Warning`]s (returned by [`Loader::load_with_warnings`]) or
+//! [`Error::UnknownKey`] errors, with the key's full path, its location and
+//! a "did you mean" suggestion drawn from the struct's field names.
+//!
+//! ## Renamed keys
+//!
+//! Fields keep accepting their former keys with
+//! `#[from_file(alias = "old_name", deprecated = "use `new_name` instead")]`.
+//! [`Loader`] maps them onto the field and reports a
+//! [`Warning::Deprecated`] naming the file that used them; setting both the
+//! old and the new key in one file is an error.
+//!
+//! ## Provenance
+//!
+//! [`Loader::load_resolved`] returns a [`Resolved`] configuration that
+//! knows the [`Source`] of every leaf field: the file and line that set it
+//! last, its `default = ...` expression, or `Default::default()`.
+//! [`Resolved::explain`] lists them all.
+//!
+//! ## Diffing
+//!
+//! Derived types also implement [`Diff`], which compares two resolved
+//! configurations field by field, recursing into nested structs:
+//!
+//! ```rust,ignore
+//! for change in AppConfig::diff(&old, &new) {
+//!     println!("{change}"); // e.g. `server.port: 8080 -> 9000`
+//! }
+//! ```
+//!
+//! Shadows implement it too, showing unset fields as [`UNSET`], so a new
+//! file can be compared with the current one before it is applied.
+//! [`ConfigDiff`] collects the changes and renders them as a report, or as
+//! JSON with the `json` feature.
+//!
+//! ## Secrets
+//!
+//! Fields marked `#[from_file(secret)]` show as [`REDACTED`] in the shadow's
+//! `Debug` output, in [`Diff`] changes and in validation errors, whose
+//! source snippet is left out too. They are left unset by
+//! [`ToShadow::to_redacted_shadow`] (for logging a configuration), and their
+//! defaults are left out of templates, schemas and references. Serializing a
+//! shadow still writes them, so it can be saved and loaded again.
+//!
+//! ## Reflection
+//!
+//! Derived types implement [`ConfigMeta`], describing their fields at
+//! runtime: key, type name, doc comment, `default = ...` expression,
+//! attributes and nested fields. [`StructMeta::flatten`] lists them with
+//! their dotted paths, so help screens or admin pages can be written once
+//! for any configuration type.
+//!
+//! [`Reference`] builds on it to write a reference of every key, grouped by
+//! section, as Markdown or as a man page ([`ReferenceFormat`]), so
+//! documentation follows the `#[from_file(...)]` attributes.
+//!
+//! ## Templates
+//!
+//! [`Template`] writes an example configuration file for a derived type in
+//! TOML, YAML or JSON5 ([`TemplateFormat`]): every key with its doc comment
+//! and default value, nested structs as sections. With
+//! [`Template::comment_out_defaults`], the keys are commented out.
+//!
+//! ```rust,ignore
+//! std::fs::write("config.example.toml", Template::new(TemplateFormat::Toml).render::<AppConfig>())?;
+//! ```
+//!
+//! ## JSON Schema
+//!
+//! With the `schema` feature, derived types whose leaf field types have a
+//! schema implement [`schema::JsonSchema`], and [`schema::json_schema`] builds a schema
+//! document for their configuration files, with doc comments, defaults,
+//! required fields and validation constraints, for editors and CI to check
+//! files against.
+//!
+//! ## Loading and reloading
+//!
+//! With the `serde` feature, [`Loader`] reads and layers configuration files
+//! (later files override earlier ones) and resolves them into your type.
+//! [`SharedConfig`] hands the active configuration to the rest of the program,
+//! and with the `signal` feature a `Reloader` re-runs the loader whenever the
+//! process receives `SIGHUP`:
+//!
+//! ```rust,ignore
+//! let loader = Loader::<AppConfig>::new().file("/etc/myapp/config.toml");
+//! let shared = SharedConfig::new(loader.load()?);
+//! let handle = shared.clone();
+//! Reloader::new(loader).spawn(move |result| match result {
+//!     Ok(config) => handle.store(config),
+//!     Err(e) => eprintln!("keeping previous config: {e}"),
+//! })?;
+//! ```
+//!
+//! Every source is parsed into a [`Value`], a format-independent tree of
+//! maps, lists and scalars, before being merged. [`Value::from_config`],
+//! [`Value::from_serialize`] (for shadows), [`Value::into_config`] and
+//! [`Value::deserialize_into`] convert between trees and your types, so
+//! custom sources and overrides need no intermediate file format.
+//!
+//! [`Loader::cache_file`] additionally persists every successfully resolved
+//! document, so [`Loader::load_or_cached`] can start from the last-known-good
+//! configuration when the primary file is corrupted.
+//!
 //! ## Feature flags
 //!
 //! - `derive`: Enables the `filecaster-derive` crate, allowing you to use `#[derive(FromFile)]`.
 //! - `serde`: Enables `serde` serialization/deserialization support for the `FromFile` trait.
 //! - `merge`: Enables `merge` crate support, allowing for merging multiple partial configurations.
+//! - `json`, `toml`: Enable the corresponding file formats in [`Loader`].
+//! - `signal` (Unix only): Enables `Reloader`, which reloads on `SIGHUP`.
+//! - `regex`: Enables the `#[from_file(regex = "...")]` validation attribute.
+//!   Patterns are compiled when deriving, so an invalid one fails the build.
+//! - `schema`: Enables the [`schema`] module and its derived implementations.
+
+#[cfg(feature = "serde")]
+mod condition;
+mod diagnostic;
+mod diff;
+mod error;
+#[cfg(feature = "serde")]
+mod loader;
+mod meta;
+#[cfg(feature = "serde")]
+mod path;
+#[cfg(feature = "serde")]
+mod profile;
+#[cfg(feature = "serde")]
+mod provenance;
+mod reference;
+mod reload;
+#[cfg(feature = "schema")]
+pub mod schema;
+mod shadow;
+#[cfg(feature = "serde")]
+mod template;
+pub mod validate;
+#[cfg(feature = "serde")]
+pub mod value;
+mod warning;
 
+pub use diagnostic::Location;
+pub use diff::{ConfigDiff, Diff, FieldChange, REDACTED, UNSET};
+#[doc(hidden)]
+pub use diff::{join_path, redact};
+pub use error::Error;
+#[doc(hidden)]
+pub use error::collect_error;
 pub use filecaster_derive::FromFile;
 #[cfg(feature = "serde")]
+pub use loader::{Format, Loader};
+pub use meta::{ConfigMeta, FieldMeta, StructMeta, UnknownKeys};
+#[cfg(feature = "serde")]
+pub use path::PathAccess;
+#[cfg(feature = "serde")]
+pub use provenance::{Resolved, Source};
+pub use reference::{Reference, ReferenceFormat};
+pub use reload::SharedConfig;
+#[cfg(all(unix, feature = "signal"))]
+pub use reload::{ReloadHandle, Reloader};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+pub use shadow::{MinimalShadow, ToShadow};
+#[cfg(feature = "serde")]
+pub use template::{Template, TemplateFormat};
+#[cfg(feature = "serde")]
+pub use value::Value;
+pub use warning::Warning;
 
 /// Marker for types that can be built from an [`Option<Shadow>`] produced by the macro.
 ///
@@ -125,7 +319,38 @@ pub trait FromFile: Sized {
     /// If `file` is `None`, a default `Shadow` instance should be used.
     /// The implementation should then populate `Self` by taking values from
     /// `file` where present, and applying defaults otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Derived implementations panic if [`FromFile::try_from_file`] fails,
+    /// such as when a `#[from_file(required)]` field is missing or a value
+    /// fails validation. Use [`FromFile::try_from_file`] for shadows read
+    /// from user-supplied files.
     fn from_file(file: Option<Self::Shadow>) -> Self;
+
+    /// Fallible counterpart of [`FromFile::from_file`].
+    ///
+    /// Derived implementations propagate errors from nested fields, prefixing
+    /// their path with the field name (see [`Error::at`]). The default
+    /// implementation never fails; override it in a manual implementation to
+    /// reject values with [`Error::invalid`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value in `file` is not acceptable for `Self`.
+    fn try_from_file(file: Option<Self::Shadow>) -> Result<Self, Error> {
+        Ok(Self::from_file(file))
+    }
+
+    /// Describes the fields of `Self::Shadow`, or `None` for leaf values.
+    ///
+    /// Derived implementations return their [`ConfigMeta::config_meta`],
+    /// listing every field with the metadata of its type; [`Loader`] uses it
+    /// to detect unknown keys.
+    #[must_use]
+    fn meta() -> Option<StructMeta> {
+        None
+    }
 }
 
 #[cfg(not(feature = "serde"))]
@@ -0,0 +1,1014 @@
+use crate::diagnostic::{Location, Segment, split_path};
+use crate::diff::{REDACTED, join_path};
+use crate::meta::{self, StructMeta, UnknownKeys};
+use crate::path::insert;
+use crate::provenance::{Resolved, Source};
+use crate::value::Value;
+use crate::{Error, FromFile, Warning, condition, profile};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Supported configuration file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Format {
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "toml")]
+    Toml,
+}
+
+impl Format {
+    /// Guesses the format from a file extension (`.json`, `.toml`).
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            #[cfg(feature = "json")]
+            "json" => Some(Self::Json),
+            #[cfg(feature = "toml")]
+            "toml" => Some(Self::Toml),
+            _ => None,
+        }
+    }
+
+    /// Parses `text`, pointing syntax errors at their position in `origin`.
+    #[cfg_attr(not(any(feature = "json", feature = "toml")), allow(unused_variables))]
+    fn parse(self, origin: &str, text: &str) -> Result<Value, Error> {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::from_str(text).map_err(|e| {
+                // The position is rendered by the location instead.
+                let message = e.to_string();
+                let message = message
+                    .rsplit_once(" at line ")
+                    .map_or(&*message, |(m, _)| m);
+                Error::Parse {
+                    origin: origin.to_string(),
+                    message: message.to_string(),
+                    location: Location::from_line_column(origin, text, e.line(), e.column())
+                        .map(Box::new),
+                }
+            }),
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::from_str(text).map_err(|e| Error::Parse {
+                origin: origin.to_string(),
+                message: e.message().to_string(),
+                location: e
+                    .span()
+                    .map(|span| Box::new(Location::from_span(origin, text, span))),
+            }),
+        }
+    }
+
+    /// Byte range of the value at `path` in `text`.
+    #[cfg_attr(not(any(feature = "json", feature = "toml")), allow(unused_variables))]
+    fn locate(self, text: &str, path: &[Segment<'_>]) -> Option<Range<usize>> {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => crate::diagnostic::json_span(text, path),
+            #[cfg(feature = "toml")]
+            Self::Toml => crate::diagnostic::toml_span(text, path),
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "json", feature = "toml")), allow(unused_variables))]
+    fn render(self, value: &Value) -> Result<String, String> {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::to_string_pretty(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// A layer that took part in a load, kept to locate errors and check keys.
+struct Document<'a> {
+    source: Layer<'a>,
+    text: String,
+    /// The parsed layer, with its profile and conditional sections applied,
+    /// its aliases renamed and, for a lenient layer, its strings coerced.
+    value: Value,
+    deprecated: Vec<meta::DeprecatedKey>,
+}
+
+/// Where a [`Document`] was read from.
+enum Layer<'a> {
+    File(&'a FileSource),
+    /// The environment variables that were set, by field path.
+    Env(Vec<(String, &'static str)>),
+}
+
+impl Layer<'_> {
+    fn origin(&self) -> String {
+        match self {
+            Self::File(file) => file.origin(),
+            Self::Env(_) => "environment".to_string(),
+        }
+    }
+
+    /// The environment variable that set the value at `path`, or one of
+    /// the sections holding it.
+    fn variable(&self, path: &str) -> Option<&'static str> {
+        let Self::Env(vars) = self else {
+            return None;
+        };
+        vars.iter().find_map(|(field, var)| {
+            let rest = path.strip_prefix(field.as_str())?;
+            (rest.is_empty() || rest.starts_with(['.', '['])).then_some(*var)
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FileSource {
+    path: PathBuf,
+    format: Option<Format>,
+    required: bool,
+}
+
+impl FileSource {
+    fn origin(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn format(&self) -> Result<Format, Error> {
+        self.format
+            .or_else(|| Format::from_path(&self.path))
+            .ok_or_else(|| Error::Parse {
+                origin: self.origin(),
+                message: "unsupported or missing file extension".to_string(),
+                location: None,
+            })
+    }
+
+    /// Reads the file, returning `None` for a missing optional file.
+    fn read(&self) -> Result<Option<String>, Error> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(text) => Ok(Some(text)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound && !self.required => Ok(None),
+            Err(source) => Err(Error::Io {
+                path: self.path.clone(),
+                source,
+            }),
+        }
+    }
+
+    /// Reads and parses the file, returning `None` for a missing optional file.
+    fn load(&self) -> Result<Option<Value>, Error> {
+        match self.read()? {
+            Some(text) => self.format()?.parse(&self.origin(), &text).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Writes `value` to the file atomically, via a sibling temporary file.
+    fn store(&self, value: &Value) -> Result<(), Warning> {
+        let failed = |message: String| Warning::Cache {
+            path: self.path.clone(),
+            message,
+        };
+        let text = self
+            .format()
+            .map_err(|error| failed(error.to_string()))?
+            .render(value)
+            .map_err(failed)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        write_private(Path::new(&tmp), &text)
+            .and_then(|()| std::fs::rename(&tmp, &self.path))
+            .map_err(|error| failed(error.to_string()))
+    }
+}
+
+/// Writes `text` to a new file at `path` that, on Unix, only its owner can
+/// read.
+fn write_private(path: &Path, text: &str) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    // Drop a leftover file, whose mode would be kept.
+    match std::fs::remove_file(path) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+    options.open(path)?.write_all(text.as_bytes())
+}
+
+/// Layers configuration files and resolves them into `T`.
+///
+/// Files are read in the order they were added; values from later files
+/// override earlier ones, with tables merged key by key. The merged document
+/// is deserialized into `T::Shadow` and turned into `T` with
+/// [`FromFile::try_from_file`].
+///
+/// Each file may contain `[profile.<name>]` sections. Once a profile is
+/// selected with [`Loader::profile`] or [`Loader::profile_env`], the rest of
+/// that file is merged over its `profile.default` section, which holds shared
+/// defaults, and the active profile's section over the result, before the
+/// file is layered with the others.
+///
+/// With [`Loader::conditional_sections`], files may also contain
+/// `[when.'<predicate>']` sections (e.g. `[when.'hostname = "web-*"']`) that
+/// are merged last, and only on machines matching the predicate.
+///
+/// With [`Loader::unknown_keys`], keys that `T` does not accept are reported
+/// as warnings ([`Loader::load_with_warnings`]) or errors.
+///
+/// With [`Loader::env`], the environment variables named by
+/// `#[from_file(env = "...")]` are layered over the files. Their strings are
+/// coerced into the numbers, booleans and lists the fields expect, as are
+/// those of the files with [`Loader::lenient`].
+///
+/// With [`Loader::cache_file`], every successfully resolved document is also
+/// persisted, and [`Loader::load_or_cached`] falls back to it when the
+/// sources themselves cannot be loaded.
+///
+/// ```rust,ignore
+/// let config: AppConfig = Loader::new()
+///     .file("/etc/myapp/config.toml")
+///     .optional_file("config.local.toml")
+///     .load()?;
+/// ```
+pub struct Loader<T> {
+    files: Vec<FileSource>,
+    cache: Option<FileSource>,
+    profile: Option<String>,
+    profile_env: Option<String>,
+    conditions: bool,
+    hostname: Option<String>,
+    vars: Option<HashMap<String, String>>,
+    unknown_keys: UnknownKeys,
+    lenient: bool,
+    env: bool,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Loader<T> {
+    /// Creates a loader without any sources.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            cache: None,
+            profile: None,
+            profile_env: None,
+            conditions: false,
+            hostname: None,
+            vars: None,
+            unknown_keys: UnknownKeys::Ignore,
+            lenient: false,
+            env: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Adds a file that must exist; its format is taken from the extension.
+    #[must_use]
+    pub fn file(self, path: impl Into<PathBuf>) -> Self {
+        self.push(path.into(), None, true)
+    }
+
+    /// Adds a file that is silently skipped when it does not exist.
+    #[must_use]
+    pub fn optional_file(self, path: impl Into<PathBuf>) -> Self {
+        self.push(path.into(), None, false)
+    }
+
+    /// Adds a required file with an explicit format, ignoring its extension.
+    #[must_use]
+    pub fn file_with_format(self, path: impl Into<PathBuf>, format: Format) -> Self {
+        self.push(path.into(), Some(format), true)
+    }
+
+    fn push(mut self, path: PathBuf, format: Option<Format>, required: bool) -> Self {
+        self.files.push(FileSource {
+            path,
+            format,
+            required,
+        });
+        self
+    }
+
+    /// Persists the last successfully resolved document to `path`.
+    ///
+    /// The format is taken from the extension. The cache is never used as a
+    /// regular source; only [`Loader::load_or_cached`] reads it back. It holds
+    /// secret fields too, so on Unix it is created readable by its owner
+    /// only (mode `0600`).
+    #[must_use]
+    pub fn cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache = Some(FileSource {
+            path: path.into(),
+            format: None,
+            // Missing until the first successful load.
+            required: false,
+        });
+        self
+    }
+
+    /// Selects the active profile, taking precedence over [`Loader::profile_env`].
+    #[must_use]
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    /// Reads the active profile from the environment variable `var` at load time.
+    ///
+    /// If the variable is unset, only the `profile.default` sections apply.
+    #[must_use]
+    pub fn profile_env(mut self, var: impl Into<String>) -> Self {
+        self.profile_env = Some(var.into());
+        self
+    }
+
+    /// The profile that the next load will apply, if any.
+    #[must_use]
+    pub fn active_profile(&self) -> Option<String> {
+        self.profile.clone().or_else(|| {
+            self.profile_env
+                .as_ref()
+                .and_then(|var| self.var(var))
+                .filter(|name| !name.is_empty())
+        })
+    }
+
+    /// Reads environment variables from `vars` instead of the process
+    /// environment, for [`Loader::profile_env`], [`Loader::env`] and
+    /// `env.<VAR>` predicates.
+    #[must_use]
+    pub fn env_vars<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let vars = vars.into_iter().map(|(k, v)| (k.into(), v.into()));
+        self.vars = Some(vars.collect());
+        self
+    }
+
+    /// The value of the environment variable `name`.
+    fn var(&self, name: &str) -> Option<String> {
+        match &self.vars {
+            Some(vars) => vars.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        }
+    }
+
+    /// Enables `[when.'<predicate>']` sections.
+    ///
+    /// A predicate compares `hostname`, `target_os`, `profile` or
+    /// `env.<VAR>` with a quoted glob pattern, e.g. `env.REGION = "eu-*"`.
+    /// Predicates combine with `all(...)`, `any(...)` and `not(...)` and may
+    /// be wrapped in `cfg(...)`. Matching sections are merged after profiles,
+    /// in the order of their keys.
+    #[must_use]
+    pub const fn conditional_sections(mut self) -> Self {
+        self.conditions = true;
+        self
+    }
+
+    /// Overrides the host name matched by `hostname = "..."` predicates,
+    /// which is otherwise detected from the system.
+    #[must_use]
+    pub fn hostname(mut self, name: impl Into<String>) -> Self {
+        self.hostname = Some(name.into());
+        self
+    }
+
+    /// Sets how keys that no field accepts are handled, for every struct
+    /// without its own `#[from_file(unknown_keys = "...")]` policy.
+    ///
+    /// Unknown keys are reported with their full path, their location and
+    /// the closest field name when it looks like a typo.
+    #[must_use]
+    pub const fn unknown_keys(mut self, policy: UnknownKeys) -> Self {
+        self.unknown_keys = policy;
+        self
+    }
+
+    /// Coerces the string values of the files into the types of the fields
+    /// they set, as for the environment variables of [`Loader::env`]:
+    ///
+    /// - numbers from `"8080"`, `"0x1F90"`, `"0o17"`, `"0b101"` or `"1_000"`;
+    /// - booleans from `"true"`/`"false"`, `"yes"`/`"no"`, `"on"`/`"off"` or
+    ///   `"1"`/`"0"`, ignoring case;
+    /// - lists from comma-separated strings, each item coerced in turn.
+    ///
+    /// Values that are already typed are taken as they are; strings that do
+    /// not parse are reported like any other mismatch.
+    ///
+    /// Each layer is coerced on its own, before it is merged, so this only
+    /// affects the files. It is off by default, as structured files state
+    /// the type of their values.
+    #[must_use]
+    pub const fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Layers the environment variables named by `#[from_file(env = "...")]`
+    /// over the files, reading them from [`Loader::env_vars`] if set.
+    ///
+    /// Environment variables only hold strings, so they are always coerced
+    /// as described for [`Loader::lenient`]: `APP_PORT=8080` sets a `u16`
+    /// field. Errors in their values name the variable.
+    #[must_use]
+    pub const fn env(mut self) -> Self {
+        self.env = true;
+        self
+    }
+
+    const fn uses_profiles(&self) -> bool {
+        self.profile.is_some() || self.profile_env.is_some()
+    }
+
+    /// Paths of all configured files, in layering order.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.path.as_path())
+    }
+}
+
+impl<T> Loader<T>
+where
+    T: FromFile,
+    T::Shadow: Serialize + DeserializeOwned,
+{
+    /// Reads every source and merges them into a single shadow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a required file cannot be read, a file cannot be
+    /// parsed, or the merged document does not match `T::Shadow`. All such
+    /// problems are reported together, as an [`Error::Multiple`] if there
+    /// is more than one.
+    pub fn load_shadow(&self) -> Result<T::Shadow, Error> {
+        let mut errors = Vec::new();
+        let (merged, documents) = self.merge_sources(&mut errors);
+        self.check_deprecated_keys(&documents, &mut errors);
+        self.check_unknown_keys(&documents, &mut errors);
+        let shadow = self.deserialize(merged, &documents, &mut errors);
+        if errors.is_empty() {
+            Ok(shadow)
+        } else {
+            Err(Error::multiple(errors))
+        }
+    }
+
+    /// Reads every source and resolves the final configuration.
+    ///
+    /// If a cache file is configured, the merged document is written to it
+    /// once resolution succeeds. Failing to write it does not fail the load;
+    /// [`Loader::load_with_warnings`] reports it as a [`Warning::Cache`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Loader::load_shadow`]. Errors from [`FromFile::try_from_file`]
+    /// and keys rejected by [`UnknownKeys::Deny`] are reported along with
+    /// them, unless a source could not be read or parsed.
+    pub fn load(&self) -> Result<T, Error> {
+        self.load_with_warnings().map(|(config, _)| config)
+    }
+
+    /// Like [`Loader::load`], but also returns the warnings found on the way,
+    /// such as deprecated keys or unknown keys under [`UnknownKeys::Warn`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Loader::load`].
+    pub fn load_with_warnings(&self) -> Result<(T, Vec<Warning>), Error> {
+        self.resolve(false).map(|resolved| {
+            let warnings = resolved.warnings().to_vec();
+            (resolved.into_inner(), warnings)
+        })
+    }
+
+    /// Like [`Loader::load_with_warnings`], but also records where the value
+    /// of every leaf field came from: the last file that set it, with its
+    /// line, or the field's default.
+    ///
+    /// # Errors
+    ///
+    /// See [`Loader::load`].
+    pub fn load_resolved(&self) -> Result<Resolved<T>, Error> {
+        self.resolve(true)
+    }
+
+    fn resolve(&self, explain: bool) -> Result<Resolved<T>, Error> {
+        let mut errors = Vec::new();
+        let (merged, documents) = self.merge_sources(&mut errors);
+        // Missing or invalid fields are meaningless while a layer is absent.
+        let sources_loaded = errors.is_empty();
+        let mut warnings = self.check_deprecated_keys(&documents, &mut errors);
+        warnings.extend(self.check_unknown_keys(&documents, &mut errors));
+        let shadow = self.deserialize(merged.clone(), &documents, &mut errors);
+        if !sources_loaded {
+            return Err(Error::multiple(errors));
+        }
+        let config = match T::try_from_file(Some(shadow)) {
+            Ok(config) if errors.is_empty() => config,
+            Ok(_) => return Err(Error::multiple(errors)),
+            Err(error) => {
+                errors.push(self.locate(error, &documents));
+                return Err(Error::multiple(errors));
+            }
+        };
+        if let Some(cache) = &self.cache
+            && let Err(warning) = cache.store(&merged)
+        {
+            warnings.push(warning);
+        }
+        let mut sources = Vec::new();
+        if explain && let Some(meta) = T::meta() {
+            self.explain(&meta, "", None, &documents, &mut sources);
+        }
+        Ok(Resolved::new(config, sources, warnings))
+    }
+
+    /// Like [`Loader::load`], but falls back to the cache file when loading
+    /// from the sources fails.
+    ///
+    /// Returns the configuration together with the error that forced the
+    /// fallback, if any, so it can be reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns the original error if no cache file is configured or there is
+    /// none yet. If the cache cannot be loaded either, its error is returned
+    /// along with the original one, as an [`Error::Multiple`].
+    pub fn load_or_cached(&self) -> Result<(T, Option<Error>), Error> {
+        let error = match self.load() {
+            Ok(config) => return Ok((config, None)),
+            Err(error) => error,
+        };
+        let Some(cache) = &self.cache else {
+            return Err(error);
+        };
+        let cached = cache
+            .load()
+            .map_err(Self::hide_secret_line)
+            .and_then(|value| {
+                value
+                    .map(|value| T::try_from_file(Some(Self::to_shadow(value)?)))
+                    .transpose()
+            });
+        match cached {
+            Ok(Some(config)) => Ok((config, Some(error))),
+            Ok(None) => Err(error),
+            Err(cache_error) => Err(Error::multiple(vec![error, cache_error])),
+        }
+    }
+
+    /// Merges every source that can be read and parsed, recording the
+    /// failures of the others in `errors`.
+    fn merge_sources(&self, errors: &mut Vec<Error>) -> (Value, Vec<Document<'_>>) {
+        let active = self.active_profile();
+        let context = self.conditions.then(|| condition::Context {
+            hostname: self.hostname.clone().or_else(condition::hostname),
+            vars: self.vars.clone(),
+            profile: active.clone(),
+        });
+        let mut merged = Value::Map(crate::value::Map::new());
+        let mut documents = Vec::new();
+        for file in &self.files {
+            match self.read_document(file, active.as_deref(), context.as_ref()) {
+                Ok(Some(document)) => {
+                    merged.merge(document.value.clone());
+                    documents.push(document);
+                }
+                Ok(None) => {}
+                Err(error) => errors.push(error),
+            }
+        }
+        if self.env
+            && let Some(document) = self.read_env()
+        {
+            merged.merge(document.value.clone());
+            documents.push(document);
+        }
+        (merged, documents)
+    }
+
+    fn read_document<'a>(
+        &self,
+        file: &'a FileSource,
+        active: Option<&str>,
+        context: Option<&condition::Context>,
+    ) -> Result<Option<Document<'a>>, Error> {
+        let Some(text) = file.read()? else {
+            return Ok(None);
+        };
+        let mut value = file
+            .format()?
+            .parse(&file.origin(), &text)
+            .map_err(Self::hide_secret_line)?;
+        if self.uses_profiles() {
+            profile::apply(&mut value, active);
+        }
+        if let Some(context) = context {
+            condition::apply(&mut value, context).map_err(|message| Error::Parse {
+                origin: file.origin(),
+                message,
+                location: None,
+            })?;
+        }
+        let deprecated = T::meta()
+            .map(|meta| meta::rename_aliases(&mut value, &meta))
+            .unwrap_or_default();
+        if self.lenient {
+            value = Self::coerce(value);
+        }
+        Ok(Some(Document {
+            source: Layer::File(file),
+            text,
+            value,
+            deprecated,
+        }))
+    }
+
+    /// The variables named by `#[from_file(env = "...")]` that are set, as a
+    /// coerced layer of their own.
+    fn read_env(&self) -> Option<Document<'static>> {
+        let meta = T::meta()?;
+        let mut value = Value::Map(crate::value::Map::new());
+        let mut vars = Vec::new();
+        for (path, field) in meta.flatten() {
+            if let Some(var) = field.env
+                && let Some(text) = self.var(var)
+                && insert(&mut value, &split_path(&path), Value::String(text)).is_ok()
+            {
+                vars.push((path, var));
+            }
+        }
+        (!vars.is_empty()).then(|| Document {
+            source: Layer::Env(vars),
+            text: String::new(),
+            value: Self::coerce(value),
+            deprecated: Vec::new(),
+        })
+    }
+
+    /// Coerces the strings of `value` into the types of the fields they set.
+    /// Those that do not fit are kept, to be reported once the layers are
+    /// merged.
+    fn coerce(value: Value) -> Value {
+        let mut fitting = value.clone();
+        loop {
+            match fitting.clone().deserialize_lenient::<T::Shadow>() {
+                Ok(shadow) => {
+                    let mut value = value;
+                    if let Ok(coerced) = Value::from_serialize(&shadow) {
+                        value.merge(coerced);
+                    }
+                    return value;
+                }
+                Err(error) => {
+                    if !remove_path(&mut fitting, &split_path(&error.path)) {
+                        return value;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deserializes `value` into `T::Shadow`. Every value of the wrong type
+    /// is recorded in `errors` and dropped, so that all of them are found.
+    fn deserialize(
+        &self,
+        mut value: Value,
+        documents: &[Document<'_>],
+        errors: &mut Vec<Error>,
+    ) -> T::Shadow {
+        // Denied unknown keys are already reported; don't let the shadow's
+        // `deny_unknown_fields` report them again.
+        for error in errors.iter() {
+            if let Error::UnknownKey { path, .. } = error {
+                remove_path(&mut value, &split_path(path));
+            }
+        }
+        loop {
+            let error = match Self::to_shadow(value.clone()) {
+                Ok(shadow) => return shadow,
+                Err(error) => error,
+            };
+            let removed = match &error {
+                Error::Field { path, .. } => remove_path(&mut value, &split_path(path)),
+                _ => false,
+            };
+            errors.push(self.locate(error, documents));
+            if !removed {
+                return T::Shadow::default();
+            }
+        }
+    }
+
+    /// Points field errors at the value in the last file that set it.
+    fn locate(&self, error: Error, documents: &[Document<'_>]) -> Error {
+        match error {
+            Error::Multiple { errors } => Error::Multiple {
+                errors: errors
+                    .into_iter()
+                    .map(|error| self.locate(error, documents))
+                    .collect(),
+            },
+            Error::Field { ref path, .. } => {
+                let secret = Self::is_secret(path);
+                let segments = split_path(path);
+                let found = documents.iter().rev().find_map(|document| {
+                    match document.source.variable(path) {
+                        Some(var) => Some(Err(var)),
+                        None => self.find(document, &segments).map(Ok),
+                    }
+                });
+                match found {
+                    // The line would show the secret value.
+                    Some(Ok(location)) if secret => error.with_location(location.without_snippet()),
+                    Some(Ok(location)) => error.with_location(location),
+                    Some(Err(var)) => error.with_variable(var),
+                    None => error,
+                }
+            }
+            error => error,
+        }
+    }
+
+    /// Finds the value at `path` in `document`, looking in the active
+    /// profile's section, then the base section, then `profile.default`.
+    fn find(&self, document: &Document<'_>, path: &[Segment<'_>]) -> Option<Location> {
+        let Layer::File(file) = document.source else {
+            return None;
+        };
+        if path.is_empty() {
+            return None;
+        }
+        let active = self.active_profile();
+        let mut prefixes = Vec::new();
+        if self.uses_profiles()
+            && let Some(active) = &active
+        {
+            prefixes.push(vec![
+                Segment::Key(profile::PROFILE_KEY),
+                Segment::Key(active),
+            ]);
+        }
+        prefixes.push(Vec::new());
+        if self.uses_profiles() {
+            prefixes.push(vec![
+                Segment::Key(profile::PROFILE_KEY),
+                Segment::Key(profile::DEFAULT_PROFILE),
+            ]);
+        }
+        let format = file.format().ok()?;
+        prefixes.iter().find_map(|prefix| {
+            let full = prefix.iter().chain(path).copied().collect::<Vec<_>>();
+            let span = format.locate(&document.text, &full)?;
+            Some(Location::from_span(file.origin(), &document.text, span))
+        })
+    }
+
+    /// Records the source of every leaf field of `meta` under `prefix`;
+    /// `inherited` is the default expression of an enclosing field that no
+    /// document set.
+    fn explain(
+        &self,
+        meta: &StructMeta,
+        prefix: &str,
+        inherited: Option<&'static str>,
+        documents: &[Document<'_>],
+        sources: &mut Vec<(String, Source)>,
+    ) {
+        for field in &meta.fields {
+            let path = join_path(prefix, field.name);
+            let segments = split_path(&path);
+            let document = documents
+                .iter()
+                .rev()
+                .find(|document| lookup(&document.value, &segments).is_some());
+            let default = if document.is_some() {
+                None
+            } else {
+                inherited.or(field.default)
+            };
+            if let Some(nested) = &field.nested {
+                self.explain(nested, &path, default, documents, sources);
+                continue;
+            }
+            let source = match (document, default) {
+                (Some(document), _) => match document.source.variable(&path) {
+                    Some(var) => Source::Env(var),
+                    None => Source::File {
+                        origin: document.source.origin(),
+                        location: self.find(document, &segments).map(Box::new),
+                    },
+                },
+                (None, Some(expr)) => Source::DefaultExpr(expr),
+                (None, None) => Source::Default,
+            };
+            sources.push((path, source));
+        }
+    }
+
+    /// Reports the deprecated keys used by every document as warnings, or as
+    /// errors in `errors` if the key replacing an alias is set as well.
+    fn check_deprecated_keys(
+        &self,
+        documents: &[Document<'_>],
+        errors: &mut Vec<Error>,
+    ) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        for document in documents {
+            for key in &document.deprecated {
+                let location = self.find(document, &split_path(&key.path)).map(Box::new);
+                match &key.replacement {
+                    Some(replacement) if key.conflict => errors.push(Error::Field {
+                        path: key.path.clone(),
+                        message: format!(
+                            "cannot be combined with `{replacement}`, which replaces it"
+                        ),
+                        location,
+                    }),
+                    replacement => warnings.push(Warning::Deprecated {
+                        path: key.path.clone(),
+                        replacement: replacement.clone(),
+                        note: key.note.map(str::to_string),
+                        origin: document.source.origin(),
+                        location,
+                    }),
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Reports the keys of every document that `T` does not accept, as
+    /// warnings or, in structs that deny them, in `errors`.
+    fn check_unknown_keys(
+        &self,
+        documents: &[Document<'_>],
+        errors: &mut Vec<Error>,
+    ) -> Vec<Warning> {
+        let Some(meta) = T::meta() else {
+            return Vec::new();
+        };
+        let mut warnings = Vec::new();
+        for document in documents {
+            for key in meta::unknown_keys(&document.value, &meta, self.unknown_keys) {
+                let location = self.find(document, &split_path(&key.path)).map(Box::new);
+                let suggestion = key.suggestion.map(str::to_string);
+                if key.policy == UnknownKeys::Deny {
+                    errors.push(Error::UnknownKey {
+                        path: key.path,
+                        suggestion,
+                        location,
+                    });
+                } else {
+                    warnings.push(Warning::UnknownKey {
+                        path: key.path,
+                        suggestion,
+                        location,
+                    });
+                }
+            }
+        }
+        warnings
+    }
+
+    fn to_shadow(value: Value) -> Result<T::Shadow, Error> {
+        value
+            .deserialize_into()
+            .map_err(|error| match Error::from(error) {
+                Error::Field {
+                    path,
+                    message,
+                    location,
+                } if Self::is_secret(&path) => Error::Field {
+                    message: secret_message(&message),
+                    path,
+                    location,
+                },
+                error => error,
+            })
+    }
+
+    /// Whether the field at `path` is, or lies below, a secret one.
+    fn is_secret(path: &str) -> bool {
+        T::meta().is_some_and(|meta| meta.is_secret(path))
+    }
+
+    /// Drops the snippet of a syntax error on a line that mentions a secret
+    /// field, as the unparsed line may hold its value.
+    fn hide_secret_line(error: Error) -> Error {
+        let Error::Parse {
+            origin,
+            message,
+            location: Some(location),
+        } = error
+        else {
+            return error;
+        };
+        let secret = T::meta().is_some_and(|meta| {
+            meta.flatten().iter().any(|(path, field)| {
+                meta.is_secret(path) && mentions(&location.snippet, field.name)
+            })
+        });
+        Error::Parse {
+            origin,
+            message,
+            location: Some(if secret {
+                Box::new(location.without_snippet())
+            } else {
+                location
+            }),
+        }
+    }
+}
+
+/// Serde's `message` about a secret field, keeping only the expected type:
+/// the rejected value would show in `invalid type: integer \`-5\`, ...`.
+fn secret_message(message: &str) -> String {
+    message.rsplit_once(", expected ").map_or_else(
+        || format!("{REDACTED} is not valid"),
+        |(_, expected)| format!("{REDACTED} is not valid, expected {expected}"),
+    )
+}
+
+/// Whether `line` contains `name` as a whole word.
+fn mentions(line: &str, name: &str) -> bool {
+    line.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .any(|word| word == name)
+}
+
+/// The non-null value at `path`, if any.
+pub(crate) fn lookup<'v>(value: &'v Value, path: &[Segment<'_>]) -> Option<&'v Value> {
+    let value = path
+        .iter()
+        .try_fold(value, |value, segment| match (value, segment) {
+            (Value::Map(map), Segment::Key(key)) => map.get(*key),
+            (Value::List(list), Segment::Index(index)) => list.get(*index),
+            _ => None,
+        })?;
+    (*value != Value::Null).then_some(value)
+}
+
+/// Removes the map entry holding the value at `path`; a list is removed as a
+/// whole, as dropping one element would shift the others.
+fn remove_path(value: &mut Value, path: &[Segment<'_>]) -> bool {
+    let (Some(Segment::Key(key)), Value::Map(map)) = (path.first(), value) else {
+        return false;
+    };
+    match path.get(1) {
+        Some(Segment::Key(_)) => map
+            .get_mut(*key)
+            .is_some_and(|child| remove_path(child, &path[1..])),
+        _ => map.remove(*key).is_some(),
+    }
+}
+
+impl<T> Default for Loader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for Loader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            files: self.files.clone(),
+            cache: self.cache.clone(),
+            profile: self.profile.clone(),
+            profile_env: self.profile_env.clone(),
+            conditions: self.conditions,
+            hostname: self.hostname.clone(),
+            vars: self.vars.clone(),
+            unknown_keys: self.unknown_keys,
+            lenient: self.lenient,
+            env: self.env,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for Loader<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loader")
+            .field("files", &self.files)
+            .field("cache", &self.cache)
+            .field("profile", &self.profile)
+            .field("profile_env", &self.profile_env)
+            .field("conditions", &self.conditions)
+            .field("hostname", &self.hostname)
+            .field("unknown_keys", &self.unknown_keys)
+            .field("lenient", &self.lenient)
+            .field("env", &self.env)
+            // `vars` is left out: environment variables may hold secrets.
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,508 @@
+//! Static description of derived configuration structs.
+
+use crate::FromFile;
+use crate::diff::join_path;
+#[cfg(feature = "serde")]
+use crate::value::Value;
+
+/// What to do with keys that no field of a struct accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnknownKeys {
+    /// Drop them silently, like the shadow's `Deserialize` impl does.
+    #[default]
+    Ignore,
+    /// Report them as [`Warning`](crate::Warning)s and carry on.
+    Warn,
+    /// Fail the load with [`Error::UnknownKey`](crate::Error::UnknownKey).
+    Deny,
+}
+
+/// Static description of a configuration type: its fields, their types,
+/// doc comments, defaults and attributes, recursing into nested structs.
+///
+/// Implemented by `#[derive(FromFile)]`. Help screens, templates and admin
+/// interfaces can be written once against this trait and work for any
+/// derived type.
+///
+/// ```rust,ignore
+/// for (path, field) in AppConfig::config_meta().flatten() {
+///     println!("{path}: {}", field.type_name);
+/// }
+/// ```
+pub trait ConfigMeta: FromFile {
+    fn config_meta() -> StructMeta;
+}
+
+/// The fields of a struct deriving `FromFile`, as returned by
+/// [`ConfigMeta::config_meta`] and [`FromFile::meta`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StructMeta {
+    /// The name of the struct, e.g. `AppConfig`.
+    pub type_name: &'static str,
+    /// Fields in declaration order.
+    pub fields: Vec<FieldMeta>,
+    /// Policy declared with `#[from_file(unknown_keys = "...")]`, if any.
+    pub unknown_keys: Option<UnknownKeys>,
+    /// The struct's doc comment.
+    pub doc: Option<&'static str>,
+}
+
+impl StructMeta {
+    #[must_use]
+    pub const fn new(fields: Vec<FieldMeta>) -> Self {
+        Self {
+            type_name: "",
+            fields,
+            unknown_keys: None,
+            doc: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn type_name(mut self, name: &'static str) -> Self {
+        self.type_name = name;
+        self
+    }
+
+    #[must_use]
+    pub const fn unknown_keys(mut self, policy: UnknownKeys) -> Self {
+        self.unknown_keys = Some(policy);
+        self
+    }
+
+    #[must_use]
+    pub const fn doc(mut self, doc: &'static str) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    /// The field accepting `key`, if any.
+    #[must_use]
+    pub fn field(&self, key: &str) -> Option<&FieldMeta> {
+        self.fields.iter().find(|field| field.name == key)
+    }
+
+    /// Whether the value at the dotted `path`, such as `db.password` or
+    /// `servers[0].key`, lies in or below a `#[from_file(secret)]` field.
+    #[must_use]
+    pub fn is_secret(&self, path: &str) -> bool {
+        let mut meta = Some(self);
+        for key in path.split('.') {
+            let key = key.split_once('[').map_or(key, |(key, _)| key);
+            let Some(field) = meta.and_then(|meta| meta.field(key)) else {
+                return false;
+            };
+            if field.secret {
+                return true;
+            }
+            meta = field.nested.as_ref();
+        }
+        false
+    }
+
+    /// Every field, nested ones included, with its dotted path, e.g.
+    /// `server.port`. Each struct field comes before its own fields.
+    #[must_use]
+    pub fn flatten(&self) -> Vec<(String, &FieldMeta)> {
+        let mut fields = Vec::new();
+        self.collect_fields("", &mut fields);
+        fields
+    }
+
+    fn collect_fields<'a>(&'a self, path: &str, fields: &mut Vec<(String, &'a FieldMeta)>) {
+        for field in &self.fields {
+            let path = join_path(path, field.name);
+            fields.push((path.clone(), field));
+            if let Some(nested) = &field.nested {
+                nested.collect_fields(&path, fields);
+            }
+        }
+    }
+}
+
+/// One field of a [`StructMeta`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct FieldMeta {
+    /// The key of the field in configuration files.
+    pub name: &'static str,
+    /// The field's type as written in the struct, e.g. `Vec<String>`.
+    pub type_name: &'static str,
+    /// The fields of the field's type, if it is a derived struct itself.
+    pub nested: Option<StructMeta>,
+    /// The field's doc comment.
+    pub doc: Option<&'static str>,
+    /// The `#[from_file(default = ...)]` expression, as written.
+    pub default: Option<&'static str>,
+    /// Whether the field is `#[from_file(required)]`.
+    pub required: bool,
+    /// Whether the field is `#[from_file(secret)]`; its value is never shown.
+    pub secret: bool,
+    /// Validation options of the field, as written, e.g. `range = 1..=65535`.
+    pub constraints: &'static [&'static str],
+    /// Former keys of the field, declared with `#[from_file(alias = "...")]`.
+    pub aliases: &'static [&'static str],
+    /// Note declared with `#[from_file(deprecated = "...")]`.
+    pub deprecated: Option<&'static str>,
+    /// Environment variable declared with `#[from_file(env = "...")]`.
+    pub env: Option<&'static str>,
+}
+
+impl FieldMeta {
+    #[must_use]
+    pub const fn new(name: &'static str, nested: Option<StructMeta>) -> Self {
+        Self {
+            name,
+            type_name: "",
+            nested,
+            doc: None,
+            default: None,
+            required: false,
+            secret: false,
+            constraints: &[],
+            aliases: &[],
+            deprecated: None,
+            env: None,
+        }
+    }
+
+    #[must_use]
+    pub const fn type_name(mut self, name: &'static str) -> Self {
+        self.type_name = name;
+        self
+    }
+
+    #[must_use]
+    pub const fn doc(mut self, doc: &'static str) -> Self {
+        self.doc = Some(doc);
+        self
+    }
+
+    #[must_use]
+    pub const fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn default(mut self, expr: &'static str) -> Self {
+        self.default = Some(expr);
+        self
+    }
+
+    #[must_use]
+    pub const fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    #[must_use]
+    pub const fn constraints(mut self, constraints: &'static [&'static str]) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    #[must_use]
+    pub const fn aliases(mut self, aliases: &'static [&'static str]) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    #[must_use]
+    pub const fn deprecated(mut self, note: &'static str) -> Self {
+        self.deprecated = Some(note);
+        self
+    }
+
+    #[must_use]
+    pub const fn env(mut self, name: &'static str) -> Self {
+        self.env = Some(name);
+        self
+    }
+}
+
+/// A key of a document that no field accepts.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey {
+    pub path: String,
+    pub suggestion: Option<&'static str>,
+    pub policy: UnknownKeys,
+}
+
+/// Lists the keys of `document` that `meta` does not accept and whose
+/// struct does not ignore them; `fallback` applies to structs without a
+/// policy of their own.
+#[cfg(feature = "serde")]
+pub fn unknown_keys(document: &Value, meta: &StructMeta, fallback: UnknownKeys) -> Vec<UnknownKey> {
+    let mut found = Vec::new();
+    collect_unknown(document, meta, fallback, "", &mut found);
+    found
+}
+
+#[cfg(feature = "serde")]
+fn collect_unknown(
+    value: &Value,
+    meta: &StructMeta,
+    fallback: UnknownKeys,
+    path: &str,
+    found: &mut Vec<UnknownKey>,
+) {
+    let Value::Map(map) = value else {
+        return;
+    };
+    let policy = meta.unknown_keys.unwrap_or(fallback);
+    for (key, value) in map {
+        match meta.field(key) {
+            Some(field) => {
+                if let Some(nested) = &field.nested {
+                    collect_unknown(value, nested, fallback, &join_path(path, key), found);
+                }
+            }
+            None if policy == UnknownKeys::Ignore => {}
+            None => found.push(UnknownKey {
+                path: join_path(path, key),
+                suggestion: suggest(key, meta.fields.iter().map(|field| field.name)),
+                policy,
+            }),
+        }
+    }
+}
+
+/// A deprecated key used by a document: an alias, or a field marked
+/// deprecated without aliases.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeprecatedKey {
+    pub path: String,
+    /// Path of the field an alias was renamed to.
+    pub replacement: Option<String>,
+    pub note: Option<&'static str>,
+    /// Whether the replacement was set as well; the alias was dropped.
+    pub conflict: bool,
+}
+
+/// Renames the aliases used in `document` to the keys of their fields and
+/// lists every deprecated key found on the way.
+#[cfg(feature = "serde")]
+pub fn rename_aliases(document: &mut Value, meta: &StructMeta) -> Vec<DeprecatedKey> {
+    let mut found = Vec::new();
+    collect_aliases(document, meta, "", &mut found);
+    found
+}
+
+#[cfg(feature = "serde")]
+fn collect_aliases(
+    value: &mut Value,
+    meta: &StructMeta,
+    path: &str,
+    found: &mut Vec<DeprecatedKey>,
+) {
+    let Value::Map(map) = value else {
+        return;
+    };
+    for field in &meta.fields {
+        let replacement = join_path(path, field.name);
+        for alias in field.aliases {
+            let Some(value) = map.remove(*alias) else {
+                continue;
+            };
+            let conflict = map.contains_key(field.name);
+            if !conflict {
+                map.insert(field.name.to_string(), value);
+            }
+            found.push(DeprecatedKey {
+                path: join_path(path, alias),
+                replacement: Some(replacement.clone()),
+                note: field.deprecated,
+                conflict,
+            });
+        }
+        if field.aliases.is_empty() && field.deprecated.is_some() && map.contains_key(field.name) {
+            found.push(DeprecatedKey {
+                path: replacement.clone(),
+                replacement: None,
+                note: field.deprecated,
+                conflict: false,
+            });
+        }
+        if let (Some(nested), Some(value)) = (&field.nested, map.get_mut(field.name)) {
+            collect_aliases(value, nested, &replacement, found);
+        }
+    }
+}
+
+/// The candidate closest to `key`, if it is close enough to be a typo.
+#[cfg(feature = "serde")]
+pub(crate) fn suggest(
+    key: &str,
+    candidates: impl Iterator<Item = &'static str>,
+) -> Option<&'static str> {
+    let limit = (key.chars().count() / 3).max(2);
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between `a` and `b`, counted in characters.
+#[cfg(feature = "serde")]
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::value::tests::map;
+
+    fn meta() -> StructMeta {
+        StructMeta::new(vec![
+            FieldMeta::new("name", None),
+            FieldMeta::new(
+                "server",
+                Some(StructMeta::new(vec![
+                    FieldMeta::new("host", None),
+                    FieldMeta::new("port", None),
+                ])),
+            ),
+        ])
+    }
+
+    #[test]
+    fn secret_paths() {
+        let mut meta = meta();
+        meta.fields[1].nested.as_mut().unwrap().fields[0] = FieldMeta::new("host", None).secret();
+        assert!(meta.is_secret("server.host"));
+        assert!(!meta.is_secret("server.port"));
+        assert!(!meta.is_secret("missing.host"));
+
+        meta.fields[1].secret = true;
+        assert!(meta.is_secret("server"));
+        assert!(meta.is_secret("server.port"));
+        assert!(meta.is_secret("server[0].port"));
+        assert!(!meta.is_secret("name"));
+    }
+
+    #[test]
+    fn aliases_are_renamed() {
+        let mut doc = map([
+            ("nom", Value::String("app".into())),
+            (
+                "server",
+                map([("old_port", Value::Integer(1)), ("port", Value::Integer(2))]),
+            ),
+        ]);
+        let mut meta = meta();
+        meta.fields[0] = FieldMeta::new("name", None).aliases(&["nom"]);
+        meta.fields[1].nested.as_mut().unwrap().fields[1] = FieldMeta::new("port", None)
+            .aliases(&["old_port"])
+            .deprecated("use `port`");
+        let found = rename_aliases(&mut doc, &meta);
+        assert_eq!(
+            found,
+            vec![
+                DeprecatedKey {
+                    path: "nom".to_string(),
+                    replacement: Some("name".to_string()),
+                    note: None,
+                    conflict: false,
+                },
+                DeprecatedKey {
+                    path: "server.old_port".to_string(),
+                    replacement: Some("server.port".to_string()),
+                    note: Some("use `port`"),
+                    conflict: true,
+                },
+            ]
+        );
+        assert_eq!(
+            doc,
+            map([
+                ("name", Value::String("app".into())),
+                ("server", map([("port", Value::Integer(2))])),
+            ])
+        );
+    }
+
+    #[test]
+    fn deprecated_fields_without_aliases() {
+        let mut doc = map([("name", Value::String("app".into()))]);
+        let mut meta = meta();
+        meta.fields[0] = FieldMeta::new("name", None).deprecated("unused");
+        let found = rename_aliases(&mut doc, &meta);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, "name");
+        assert_eq!(found[0].replacement, None);
+        assert!(rename_aliases(&mut map([]), &meta).is_empty());
+    }
+
+    #[test]
+    fn edit_distances() {
+        assert_eq!(edit_distance("port", "port"), 0);
+        assert_eq!(edit_distance("prot", "port"), 2);
+        assert_eq!(edit_distance("hots", "host"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn suggestions_need_to_be_close() {
+        let names = ["host", "port", "timeout"];
+        assert_eq!(suggest("prot", names.into_iter()), Some("port"));
+        assert_eq!(suggest("timeuot", names.into_iter()), Some("timeout"));
+        assert_eq!(suggest("database", names.into_iter()), None);
+    }
+
+    #[test]
+    fn unknown_keys_are_found_with_full_paths() {
+        let doc = map([
+            ("name", Value::String("app".into())),
+            ("nmae", Value::String("typo".into())),
+            ("server", map([("prot", Value::Integer(1))])),
+        ]);
+        let found = unknown_keys(&doc, &meta(), UnknownKeys::Warn);
+        assert_eq!(
+            found,
+            vec![
+                UnknownKey {
+                    path: "nmae".to_string(),
+                    suggestion: Some("name"),
+                    policy: UnknownKeys::Warn,
+                },
+                UnknownKey {
+                    path: "server.prot".to_string(),
+                    suggestion: Some("port"),
+                    policy: UnknownKeys::Warn,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn container_policy_overrides_fallback() {
+        let doc = map([("server", map([("prot", Value::Integer(1))]))]);
+        let mut meta = meta();
+        meta.fields[1].nested = meta.fields[1]
+            .nested
+            .take()
+            .map(|nested| nested.unknown_keys(UnknownKeys::Deny));
+        let found = unknown_keys(&doc, &meta, UnknownKeys::Ignore);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].policy, UnknownKeys::Deny);
+        assert!(unknown_keys(&doc, &self::meta(), UnknownKeys::Ignore).is_empty());
+    }
+}
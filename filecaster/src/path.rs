@@ -0,0 +1,128 @@
+//! Access to the fields of a shadow by dotted key path.
+
+use crate::Error;
+use crate::diagnostic::{Segment, split_path};
+use crate::diff::join_path;
+use crate::loader::lookup;
+use crate::meta::{StructMeta, suggest};
+use crate::value::{Map, Value};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Reading and writing the fields of a shadow by dotted key path, such as
+/// `server.tls.port` or `upstreams[0].host`, for `config get`/`config set`
+/// commands and admin interfaces.
+///
+/// Implemented by `#[derive(FromFile)]` for the shadow, checking paths
+/// against the fields of the struct.
+///
+/// ```rust,ignore
+/// let mut shadow = AppConfigFile::default();
+/// shadow.set_path("server.tls.port", "9443")?;
+/// assert_eq!(shadow.get_path("server.tls.port"), Some(Value::Integer(9443)));
+/// ```
+pub trait PathAccess: Serialize + DeserializeOwned {
+    /// The fields that paths are checked against.
+    fn path_meta() -> StructMeta;
+
+    /// The value at `path`, or `None` if it is not set.
+    fn get_path(&self, path: &str) -> Option<Value> {
+        let tree = Value::from_serialize(self).ok()?;
+        lookup(&tree, &split_path(path)).cloned()
+    }
+
+    /// Sets the value at `path`, creating the sections on the way;
+    /// [`Value::Null`] unsets it. Strings are parsed into the type of the
+    /// field, as with [`Loader::lenient`](crate::Loader::lenient), so
+    /// `"9443"` sets a `u16`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownKey`] if no field has the path and
+    /// [`Error::Field`] if the value does not fit the field. `self` is left
+    /// unchanged.
+    fn set_path(&mut self, path: &str, value: impl Into<Value>) -> Result<(), Error> {
+        let segments = split_path(path);
+        check_path(&Self::path_meta(), path, &segments)?;
+        let mut tree = Value::from_serialize(self)?;
+        insert(&mut tree, &segments, value.into()).map_err(|message| Error::Field {
+            path: path.to_string(),
+            message,
+            location: None,
+        })?;
+        *self = tree.deserialize_lenient()?;
+        Ok(())
+    }
+}
+
+/// Checks that `segments` name a field of `meta`; below a leaf field, such
+/// as a map or a list, any path is accepted.
+fn check_path(meta: &StructMeta, path: &str, segments: &[Segment<'_>]) -> Result<(), Error> {
+    if segments.is_empty() {
+        return Err(Error::Field {
+            path: path.to_string(),
+            message: "expected a key path".to_string(),
+            location: None,
+        });
+    }
+    let mut meta = Some(meta);
+    let mut parent = String::new();
+    for segment in segments {
+        let Some(current) = meta else {
+            break;
+        };
+        let Segment::Key(key) = *segment else {
+            return Err(Error::Field {
+                path: parent,
+                message: "is a section, not a list".to_string(),
+                location: None,
+            });
+        };
+        let Some(field) = current.field(key) else {
+            let names = current.fields.iter().map(|field| field.name);
+            return Err(Error::UnknownKey {
+                path: join_path(&parent, key),
+                suggestion: suggest(key, names).map(str::to_string),
+                location: None,
+            });
+        };
+        parent = join_path(&parent, key);
+        meta = field.nested.as_ref();
+    }
+    Ok(())
+}
+
+/// Puts `value` at `segments` below `tree`, turning what lies on the way into
+/// maps, or unset values into lists; a list index may point one past the end
+/// to append.
+pub(crate) fn insert(
+    tree: &mut Value,
+    segments: &[Segment<'_>],
+    value: Value,
+) -> Result<(), String> {
+    let Some((segment, rest)) = segments.split_first() else {
+        *tree = value;
+        return Ok(());
+    };
+    match (segment, tree) {
+        (Segment::Key(key), Value::Map(map)) => {
+            let entry = map.entry((*key).to_string()).or_insert(Value::Null);
+            insert(entry, rest, value)
+        }
+        (Segment::Key(_), tree) => {
+            *tree = Value::Map(Map::new());
+            insert(tree, segments, value)
+        }
+        (Segment::Index(index), Value::List(list)) if *index <= list.len() => {
+            if *index == list.len() {
+                list.push(Value::Null);
+            }
+            insert(&mut list[*index], rest, value)
+        }
+        (Segment::Index(_), tree @ Value::Null) => {
+            *tree = Value::List(Vec::new());
+            insert(tree, segments, value)
+        }
+        (Segment::Index(index), _) => Err(format!("no element at index {index}")),
+    }
+}
@@ -0,0 +1,108 @@
+//! `[profile.<name>]` sections inside a single configuration document.
+
+use crate::value::Value;
+
+/// Key of the table holding the named profiles.
+pub const PROFILE_KEY: &str = "profile";
+/// Profile that is applied regardless of the active one.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Resolves the profile sections of one document in place.
+///
+/// The `profile` table is removed, then the remaining base section is merged
+/// over `profile.default`, and `profile.<active>` over the result. A
+/// `profile` key that is not a table is an ordinary value and left alone.
+pub fn apply(document: &mut Value, active: Option<&str>) {
+    let Value::Map(map) = document else {
+        return;
+    };
+    if !matches!(map.get(PROFILE_KEY), Some(Value::Map(_))) {
+        return;
+    }
+    let Some(Value::Map(mut profiles)) = map.remove(PROFILE_KEY) else {
+        return;
+    };
+    if let Some(mut defaults) = profiles.remove(DEFAULT_PROFILE) {
+        defaults.merge(std::mem::replace(document, Value::Null));
+        *document = defaults;
+    }
+    if let Some(selected) = active.and_then(|name| profiles.remove(name)) {
+        document.merge(selected);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::tests::map;
+
+    fn document() -> Value {
+        map([
+            ("port", Value::Integer(1)),
+            ("host", Value::String("base".into())),
+            (
+                "profile",
+                map([
+                    ("default", map([("port", Value::Integer(2))])),
+                    ("prod", map([("host", Value::String("prod".into()))])),
+                ]),
+            ),
+        ])
+    }
+
+    #[test]
+    fn default_profile_applies_without_selection() {
+        let mut doc = document();
+        apply(&mut doc, None);
+        assert_eq!(
+            doc,
+            map([
+                ("port", Value::Integer(1)),
+                ("host", Value::String("base".into())),
+            ])
+        );
+    }
+
+    #[test]
+    fn default_profile_fills_in_missing_keys() {
+        let mut doc = map([(
+            "profile",
+            map([("default", map([("port", Value::Integer(2))]))]),
+        )]);
+        apply(&mut doc, None);
+        assert_eq!(doc, map([("port", Value::Integer(2))]));
+    }
+
+    #[test]
+    fn non_table_profile_key_is_kept() {
+        let mut doc = map([("profile", Value::String("gaming".into()))]);
+        apply(&mut doc, Some("prod"));
+        assert_eq!(doc, map([("profile", Value::String("gaming".into()))]));
+    }
+
+    #[test]
+    fn active_profile_overrides_default_and_base() {
+        let mut doc = document();
+        apply(&mut doc, Some("prod"));
+        assert_eq!(
+            doc,
+            map([
+                ("port", Value::Integer(1)),
+                ("host", Value::String("prod".into())),
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_profile_only_strips_sections() {
+        let mut doc = document();
+        apply(&mut doc, Some("staging"));
+        assert_eq!(
+            doc,
+            map([
+                ("port", Value::Integer(1)),
+                ("host", Value::String("base".into())),
+            ])
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Where the values of a loaded configuration came from.
+
+use crate::diagnostic::Location;
+use crate::warning::Warning;
+use std::fmt::{self, Display};
+use std::ops::Deref;
+
+/// The origin of one resolved value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Source {
+    /// The field's `#[from_file(default = ...)]` expression, as written, or
+    /// that of an enclosing struct field that no file set.
+    DefaultExpr(&'static str),
+    /// `Default::default()` of the field's type.
+    Default,
+    /// The last file that set the value.
+    File {
+        origin: String,
+        location: Option<Box<Location>>,
+    },
+    /// The environment variable named by the field's
+    /// `#[from_file(env = "...")]`, read by [`Loader::env`](crate::Loader::env).
+    Env(&'static str),
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DefaultExpr(expr) => write!(f, "default `{expr}`"),
+            Self::Default => f.write_str("Default::default()"),
+            Self::File {
+                location: Some(location),
+                ..
+            } => write!(
+                f,
+                "{}:{}:{}",
+                location.origin, location.line, location.column
+            ),
+            Self::File { origin, .. } => f.write_str(origin),
+            Self::Env(var) => write!(f, "environment variable `{var}`"),
+        }
+    }
+}
+
+/// A configuration returned by [`Loader::load_resolved`](crate::Loader::load_resolved),
+/// together with the [`Source`] of every leaf field.
+///
+/// Dereferences to the configuration itself.
+#[derive(Debug, Clone)]
+pub struct Resolved<T> {
+    config: T,
+    sources: Vec<(String, Source)>,
+    warnings: Vec<Warning>,
+}
+
+impl<T> Resolved<T> {
+    pub(crate) const fn new(
+        config: T,
+        sources: Vec<(String, Source)>,
+        warnings: Vec<Warning>,
+    ) -> Self {
+        Self {
+            config,
+            sources,
+            warnings,
+        }
+    }
+
+    /// Discards the provenance and returns the configuration.
+    pub fn into_inner(self) -> T {
+        self.config
+    }
+
+    /// The source of the value at the dotted `path`, such as `server.port`.
+    #[must_use]
+    pub fn source(&self, path: &str) -> Option<&Source> {
+        self.sources
+            .iter()
+            .find(|(leaf, _)| leaf == path)
+            .map(|(_, source)| source)
+    }
+
+    /// Every leaf path with its source, in declaration order.
+    pub fn sources(&self) -> impl Iterator<Item = (&str, &Source)> {
+        self.sources
+            .iter()
+            .map(|(path, source)| (path.as_str(), source))
+    }
+
+    /// The warnings found while loading, as from
+    /// [`Loader::load_with_warnings`](crate::Loader::load_with_warnings).
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// One `path: source` line per leaf field.
+    #[must_use]
+    pub fn explain(&self) -> String {
+        self.sources
+            .iter()
+            .map(|(path, source)| format!("{path}: {source}\n"))
+            .collect()
+    }
+}
+
+impl<T> Deref for Resolved<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.config
+    }
+}
@@ -0,0 +1,204 @@
+//! Configuration reference documents generated from [`ConfigMeta`].
+
+use crate::meta::{ConfigMeta, FieldMeta, StructMeta};
+use std::fmt::Write;
+
+/// Format of a [`Reference`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReferenceFormat {
+    Markdown,
+    /// A section 5 man page.
+    Roff,
+}
+
+/// Generates a reference of every key of a configuration type: its path,
+/// type, default expression, environment variable, constraints and doc
+/// comment, grouped by nested section.
+///
+/// ```rust,ignore
+/// std::fs::write("docs/config.md", Reference::new(ReferenceFormat::Markdown).render::<AppConfig>())?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    format: ReferenceFormat,
+    title: Option<String>,
+}
+
+impl Reference {
+    #[must_use]
+    pub const fn new(format: ReferenceFormat) -> Self {
+        Self {
+            format,
+            title: None,
+        }
+    }
+
+    /// Title of the document, instead of the name of the type.
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Renders the reference of `T`.
+    #[must_use]
+    pub fn render<T: ConfigMeta>(&self) -> String {
+        let meta = T::config_meta();
+        let title = self.title.as_deref().unwrap_or(meta.type_name);
+        let mut out = String::new();
+        match self.format {
+            ReferenceFormat::Markdown => {
+                let _ = writeln!(out, "# {title}");
+                if let Some(doc) = meta.doc {
+                    let _ = write!(out, "\n{doc}\n");
+                }
+            }
+            ReferenceFormat::Roff => {
+                let _ = writeln!(out, ".TH {} 5", roff(&title.to_uppercase()));
+                let _ = write!(out, ".SH NAME\n{}", roff(title));
+                match meta.doc.and_then(|doc| doc.lines().next()) {
+                    Some(summary) => {
+                        let _ = writeln!(out, " \\- {}", roff(summary));
+                    }
+                    None => out.push('\n'),
+                }
+                if let Some(doc) = meta.doc {
+                    let _ = write!(out, ".SH DESCRIPTION\n{}\n", roff(doc));
+                }
+                out.push_str(".SH KEYS\n");
+            }
+        }
+        self.section(&meta, "", &mut out);
+        out
+    }
+
+    /// The keys of `meta`, then each nested struct as a section of its own.
+    fn section(&self, meta: &StructMeta, path: &str, out: &mut String) {
+        let (sections, keys): (Vec<_>, Vec<_>) =
+            meta.fields.iter().partition(|field| field.nested.is_some());
+        for field in keys {
+            let path = crate::join_path(path, field.name);
+            self.entry(field, &path, false, out);
+        }
+        for field in sections {
+            let path = crate::join_path(path, field.name);
+            self.entry(field, &path, true, out);
+            if let Some(nested) = &field.nested {
+                self.section(nested, &path, out);
+            }
+        }
+    }
+
+    fn entry(&self, field: &FieldMeta, path: &str, section: bool, out: &mut String) {
+        let details = details(field, section);
+        match self.format {
+            ReferenceFormat::Markdown => {
+                let level = if section { "##" } else { "###" };
+                let _ = write!(out, "\n{level} `{path}`\n");
+                if let Some(doc) = field.doc {
+                    let _ = write!(out, "\n{doc}\n");
+                }
+                if !details.is_empty() {
+                    out.push('\n');
+                    for detail in details {
+                        let values = detail.values.iter().map(|value| {
+                            if detail.code {
+                                format!("`{value}`")
+                            } else {
+                                value.clone()
+                            }
+                        });
+                        let values = values.collect::<Vec<_>>().join(", ");
+                        let _ = writeln!(out, "- {}: {values}", detail.name);
+                    }
+                }
+            }
+            ReferenceFormat::Roff => {
+                if section {
+                    let _ = writeln!(out, ".SS {}", roff(path));
+                } else {
+                    let _ = writeln!(out, ".TP\n.B {}", roff(path));
+                }
+                let mut lines = field.doc.map(roff).into_iter().collect::<Vec<_>>();
+                lines.extend(
+                    details.into_iter().map(|detail| {
+                        format!("{}: {}", detail.name, roff(&detail.values.join(", ")))
+                    }),
+                );
+                if !lines.is_empty() {
+                    let _ = writeln!(out, "{}", lines.join("\n.br\n"));
+                }
+            }
+        }
+    }
+}
+
+/// One line of attributes under a key.
+struct Detail {
+    name: &'static str,
+    values: Vec<String>,
+    /// Whether the values are Rust code, shown as such in Markdown.
+    code: bool,
+}
+
+impl Detail {
+    fn new(
+        name: &'static str,
+        values: impl IntoIterator<Item = impl ToString>,
+        code: bool,
+    ) -> Self {
+        Self {
+            name,
+            values: values.into_iter().map(|value| value.to_string()).collect(),
+            code,
+        }
+    }
+}
+
+/// The attributes of `field`.
+fn details(field: &FieldMeta, section: bool) -> Vec<Detail> {
+    let mut details = vec![Detail::new("Type", [field.type_name], true)];
+    if field.secret {
+        details.push(Detail::new("Secret", ["yes"], false));
+    }
+    if field.required {
+        details.push(Detail::new("Required", ["yes"], false));
+    } else if field.secret {
+        // The default of a secret may be a secret itself.
+    } else if let Some(default) = field.default {
+        details.push(Detail::new("Default", [default], true));
+    } else if !section {
+        details.push(Detail::new("Default", ["Default::default()"], true));
+    }
+    if let Some(name) = field.env {
+        details.push(Detail::new("Environment", [name], true));
+    }
+    if !field.constraints.is_empty() {
+        details.push(Detail::new("Constraints", field.constraints, true));
+    }
+    if !field.aliases.is_empty() {
+        details.push(Detail::new("Aliases", field.aliases, true));
+    }
+    if let Some(note) = field.deprecated {
+        details.push(Detail::new("Deprecated", [note], false));
+    }
+    details
+}
+
+/// `text` escaped for roff, so no line is taken for a request.
+fn roff(text: &str) -> String {
+    text.replace('\\', "\\e")
+        .lines()
+        .map(|line| {
+            if line.starts_with(['.', '\'']) {
+                format!("\\&{line}")
+            } else if line.is_empty() {
+                ".sp".to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,160 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// A cheaply clonable handle to the currently active configuration.
+///
+/// Readers call [`SharedConfig::get`] to obtain a snapshot; a reload publishes
+/// a new value with [`SharedConfig::store`] without disturbing snapshots that
+/// are still in use.
+#[derive(Debug)]
+pub struct SharedConfig<T> {
+    current: Arc<RwLock<Arc<T>>>,
+}
+
+impl<T> SharedConfig<T> {
+    /// Creates a handle serving `initial`.
+    pub fn new(initial: T) -> Self {
+        Self {
+            current: Arc::new(RwLock::new(Arc::new(initial))),
+        }
+    }
+
+    /// Returns a snapshot of the current configuration.
+    #[must_use]
+    pub fn get(&self) -> Arc<T> {
+        Arc::clone(&self.current.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Publishes a new configuration to every holder of this handle.
+    pub fn store(&self, config: T) {
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(config);
+    }
+}
+
+impl<T> Clone for SharedConfig<T> {
+    fn clone(&self) -> Self {
+        Self {
+            current: Arc::clone(&self.current),
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "signal"))]
+pub use signal::{ReloadHandle, Reloader};
+
+#[cfg(all(unix, feature = "signal"))]
+mod signal {
+    use crate::{Error, FromFile, Loader, SharedConfig};
+    use serde::Serialize;
+    use serde::de::DeserializeOwned;
+    use signal_hook::consts::SIGHUP;
+    use signal_hook::iterator::{Handle, Signals};
+    use std::ffi::c_int;
+    use std::io;
+    use std::thread::{self, JoinHandle};
+
+    /// Re-reads every source of a [`Loader`] whenever the process receives a signal.
+    ///
+    /// Listens for `SIGHUP` unless configured otherwise. Each reload resolves
+    /// a fresh `T` and either hands the outcome to a callback
+    /// ([`Reloader::spawn`]) or publishes it into a [`SharedConfig`]
+    /// ([`Reloader::spawn_shared`]), which keeps serving the last good
+    /// configuration when a reload fails.
+    ///
+    /// ```rust,ignore
+    /// let (config, _) = loader.load_or_cached()?;
+    /// let shared = SharedConfig::new(config);
+    /// Reloader::new(loader).spawn_shared(shared.clone(), |e| {
+    ///     eprintln!("config reload failed, keeping previous config: {e}");
+    /// })?;
+    /// ```
+    #[derive(Debug)]
+    pub struct Reloader<T> {
+        loader: Loader<T>,
+        signals: Vec<c_int>,
+    }
+
+    impl<T> Reloader<T>
+    where
+        T: FromFile + Send + 'static,
+        T::Shadow: Serialize + DeserializeOwned,
+    {
+        /// Creates a reloader for `loader`, triggered by `SIGHUP`.
+        #[must_use]
+        pub fn new(loader: Loader<T>) -> Self {
+            Self {
+                loader,
+                signals: vec![SIGHUP],
+            }
+        }
+
+        /// Replaces the triggering signal (e.g. `SIGUSR1`).
+        #[must_use]
+        pub fn signal(mut self, signal: c_int) -> Self {
+            self.signals = vec![signal];
+            self
+        }
+
+        /// Starts a background thread that reloads on every received signal.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the signal handler cannot be registered.
+        pub fn spawn<F>(self, mut on_reload: F) -> io::Result<ReloadHandle>
+        where
+            F: FnMut(Result<T, Error>) + Send + 'static,
+        {
+            let mut signals = Signals::new(&self.signals)?;
+            let handle = signals.handle();
+            let loader = self.loader;
+            let thread = thread::Builder::new()
+                .name("filecaster-reload".to_string())
+                .spawn(move || {
+                    for _ in signals.forever() {
+                        on_reload(loader.load());
+                    }
+                })?;
+            Ok(ReloadHandle { handle, thread })
+        }
+
+        /// Like [`Reloader::spawn`], but stores every successful reload in
+        /// `shared` and passes failures to `on_error`, leaving the previous
+        /// configuration in place.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the signal handler cannot be registered.
+        pub fn spawn_shared<F>(
+            self,
+            shared: SharedConfig<T>,
+            mut on_error: F,
+        ) -> io::Result<ReloadHandle>
+        where
+            T: Sync,
+            F: FnMut(Error) + Send + 'static,
+        {
+            self.spawn(move |result| match result {
+                Ok(config) => shared.store(config),
+                Err(error) => on_error(error),
+            })
+        }
+    }
+
+    /// Controls a running [`Reloader`] thread.
+    ///
+    /// Dropping the handle leaves the thread running for the rest of the process.
+    #[derive(Debug)]
+    pub struct ReloadHandle {
+        handle: Handle,
+        thread: JoinHandle<()>,
+    }
+
+    impl ReloadHandle {
+        /// Unregisters the signal handler and waits for the thread to exit.
+        pub fn stop(self) {
+            self.handle.close();
+            // The thread only runs the user callback; a panic there has
+            // already been reported and there is nothing left to clean up.
+            let _ = self.thread.join();
+        }
+    }
+}
@@ -0,0 +1,441 @@
+//! JSON Schema generation for configuration files.
+//!
+//! With the `schema` feature, `#[derive(FromFile)]` implements [`JsonSchema`]
+//! for the struct, describing the files its shadow accepts: the schema of
+//! every field's type, its doc comment as `description`, its default value,
+//! the `required` fields and the constraints of the validation attributes
+//! (`range`, `min_len`, `max_len`, `non_empty`, `one_of` and `regex`).
+//! Struct-level constraints and `validate = ...` functions are not part of
+//! the schema.
+//!
+//! ```rust,ignore
+//! let schema = filecaster::schema::json_schema::<AppConfig>();
+//! std::fs::write("config.schema.json", serde_json::to_string_pretty(&schema)?)?;
+//! ```
+
+use crate::ToShadow;
+use serde::Serialize;
+use serde_json::{Map, json};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::ops::{Bound, RangeBounds};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A JSON Schema, or a part of one.
+pub type Schema = serde_json::Value;
+
+/// The dialect of the documents built by [`json_schema`].
+pub const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Types with a JSON Schema for their values in a configuration file.
+///
+/// Implemented for primitives, strings, paths, network addresses,
+/// `Duration`, collections and maps with string keys, and by
+/// `#[derive(FromFile)]` with the `schema` feature. A derived struct only
+/// implements it if every field type does, so leaf types of your own need a
+/// manual implementation to be part of a schema.
+pub trait JsonSchema {
+    fn json_schema() -> Schema;
+}
+
+/// The schema document of configuration files for `T`.
+#[must_use]
+pub fn json_schema<T: JsonSchema>() -> Schema {
+    let mut schema = T::json_schema();
+    if let Schema::Object(object) = &mut schema {
+        object.insert("$schema".to_string(), json!(DIALECT));
+    }
+    schema
+}
+
+/// Schema of a struct with the given `properties`.
+#[doc(hidden)]
+#[must_use]
+pub fn object(
+    description: Option<&str>,
+    deny_unknown_keys: bool,
+    properties: Vec<(&str, Schema)>,
+    required: &[&str],
+) -> Schema {
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties
+            .into_iter()
+            .map(|(key, schema)| (key.to_string(), schema))
+            .collect::<Map<_, _>>(),
+    });
+    if let Some(description) = description {
+        schema["description"] = json!(description);
+    }
+    if !required.is_empty() {
+        schema["required"] = json!(required);
+    }
+    if deny_unknown_keys {
+        schema["additionalProperties"] = json!(false);
+    }
+    schema
+}
+
+/// Schema of a field of type `T`, which defaults to `default`.
+#[doc(hidden)]
+#[must_use]
+pub fn property<T>(description: Option<&str>, default: Option<T>) -> Schema
+where
+    T: JsonSchema + ToShadow,
+    T::Shadow: Serialize,
+{
+    let mut schema = T::json_schema();
+    if let Some(description) = description {
+        schema["description"] = json!(description);
+    }
+    if let Some(default) = default.and_then(|value| serde_json::to_value(value.to_shadow()).ok()) {
+        set_default(&mut schema, &default);
+    }
+    schema
+}
+
+/// Sets `default` on `schema`, or, if it describes a struct, on each of its
+/// properties.
+fn set_default(schema: &mut Schema, default: &Schema) {
+    if default.is_null() {
+        return;
+    }
+    match (schema.get_mut("properties"), default) {
+        (Some(Schema::Object(properties)), Schema::Object(defaults)) => {
+            for (key, property) in properties {
+                if let Some(default) = defaults.get(key) {
+                    set_default(property, default);
+                }
+            }
+        }
+        _ => schema["default"] = default.clone(),
+    }
+}
+
+/// `schema` of a `#[from_file(secret)]` field, without any default, nested
+/// ones included.
+#[doc(hidden)]
+#[must_use]
+pub fn secret(mut schema: Schema) -> Schema {
+    remove_defaults(&mut schema);
+    schema["writeOnly"] = json!(true);
+    schema
+}
+
+fn remove_defaults(schema: &mut Schema) {
+    match schema {
+        Schema::Object(object) => {
+            object.remove("default");
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    // Keys of `properties` are field names, not keywords.
+                    ("properties", Schema::Object(properties)) => {
+                        properties.values_mut().for_each(remove_defaults);
+                    }
+                    (_, value) => remove_defaults(value),
+                }
+            }
+        }
+        Schema::Array(items) => items.iter_mut().for_each(remove_defaults),
+        _ => {}
+    }
+}
+
+/// `schema` marked as deprecated.
+#[doc(hidden)]
+#[must_use]
+pub fn deprecated(mut schema: Schema) -> Schema {
+    schema["deprecated"] = json!(true);
+    schema
+}
+
+/// `#[from_file(range = ...)]`: `minimum`/`exclusiveMinimum` and
+/// `maximum`/`exclusiveMaximum`.
+#[doc(hidden)]
+pub fn range<T, R>(schema: &mut Schema, range: R)
+where
+    T: Serialize,
+    R: RangeBounds<T>,
+{
+    let mut bound = |key: &str, value: &T| {
+        if let Ok(value) = serde_json::to_value(value) {
+            schema[key] = value;
+        }
+    };
+    match range.start_bound() {
+        Bound::Included(start) => bound("minimum", start),
+        Bound::Excluded(start) => bound("exclusiveMinimum", start),
+        Bound::Unbounded => {}
+    }
+    match range.end_bound() {
+        Bound::Included(end) => bound("maximum", end),
+        Bound::Excluded(end) => bound("exclusiveMaximum", end),
+        Bound::Unbounded => {}
+    }
+}
+
+/// `#[from_file(min_len = ...)]`, and `non_empty` with a `min` of 1.
+#[doc(hidden)]
+pub fn min_len(schema: &mut Schema, min: usize) {
+    if let Some(key) = length_key(schema, "minLength", "minItems", "minProperties") {
+        schema[key] = json!(min);
+    }
+}
+
+/// `#[from_file(max_len = ...)]`.
+#[doc(hidden)]
+pub fn max_len(schema: &mut Schema, max: usize) {
+    if let Some(key) = length_key(schema, "maxLength", "maxItems", "maxProperties") {
+        schema[key] = json!(max);
+    }
+}
+
+/// The keyword limiting the length of values of `schema`, by its type.
+fn length_key(
+    schema: &Schema,
+    string: &'static str,
+    array: &'static str,
+    object: &'static str,
+) -> Option<&'static str> {
+    match schema.get("type")?.as_str()? {
+        "string" => Some(string),
+        "array" => Some(array),
+        "object" => Some(object),
+        _ => None,
+    }
+}
+
+/// `#[from_file(one_of = [...])]`: `enum`.
+#[doc(hidden)]
+pub fn one_of<T: Serialize + ?Sized>(schema: &mut Schema, options: &T) {
+    if let Ok(options) = serde_json::to_value(options) {
+        schema["enum"] = options;
+    }
+}
+
+/// `#[from_file(regex = "...")]`: `pattern`.
+#[doc(hidden)]
+pub fn pattern(schema: &mut Schema, pattern: &str) {
+    schema["pattern"] = json!(pattern);
+}
+
+impl JsonSchema for bool {
+    fn json_schema() -> Schema {
+        json!({ "type": "boolean" })
+    }
+}
+
+macro_rules! bounded_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl JsonSchema for $ty {
+                fn json_schema() -> Schema {
+                    json!({ "type": "integer", "minimum": <$ty>::MIN, "maximum": <$ty>::MAX })
+                }
+            }
+        )*
+    };
+}
+
+bounded_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl JsonSchema for i128 {
+    fn json_schema() -> Schema {
+        json!({ "type": "integer" })
+    }
+}
+
+impl JsonSchema for u128 {
+    fn json_schema() -> Schema {
+        json!({ "type": "integer", "minimum": 0 })
+    }
+}
+
+impl JsonSchema for f32 {
+    fn json_schema() -> Schema {
+        json!({ "type": "number" })
+    }
+}
+
+impl JsonSchema for f64 {
+    fn json_schema() -> Schema {
+        json!({ "type": "number" })
+    }
+}
+
+impl JsonSchema for char {
+    fn json_schema() -> Schema {
+        json!({ "type": "string", "minLength": 1, "maxLength": 1 })
+    }
+}
+
+impl JsonSchema for String {
+    fn json_schema() -> Schema {
+        json!({ "type": "string" })
+    }
+}
+
+impl JsonSchema for PathBuf {
+    fn json_schema() -> Schema {
+        json!({ "type": "string" })
+    }
+}
+
+impl JsonSchema for Ipv4Addr {
+    fn json_schema() -> Schema {
+        json!({ "type": "string", "format": "ipv4" })
+    }
+}
+
+impl JsonSchema for Ipv6Addr {
+    fn json_schema() -> Schema {
+        json!({ "type": "string", "format": "ipv6" })
+    }
+}
+
+impl JsonSchema for IpAddr {
+    fn json_schema() -> Schema {
+        json!({ "type": "string", "anyOf": [{ "format": "ipv4" }, { "format": "ipv6" }] })
+    }
+}
+
+macro_rules! string {
+    ($($ty:ty),*) => {
+        $(
+            impl JsonSchema for $ty {
+                fn json_schema() -> Schema {
+                    json!({ "type": "string" })
+                }
+            }
+        )*
+    };
+}
+
+string!(SocketAddr, SocketAddrV4, SocketAddrV6);
+
+/// As serde writes it: `{ secs = 30, nanos = 0 }`.
+impl JsonSchema for Duration {
+    fn json_schema() -> Schema {
+        json!({
+            "type": "object",
+            "properties": {
+                "secs": u64::json_schema(),
+                "nanos": { "type": "integer", "minimum": 0, "maximum": 999_999_999 },
+            },
+            "required": ["secs", "nanos"],
+        })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Option<T> {
+    fn json_schema() -> Schema {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Box<T> {
+    fn json_schema() -> Schema {
+        T::json_schema()
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for Vec<T> {
+    fn json_schema() -> Schema {
+        json!({ "type": "array", "items": T::json_schema() })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for VecDeque<T> {
+    fn json_schema() -> Schema {
+        Vec::<T>::json_schema()
+    }
+}
+
+impl<T: JsonSchema, const N: usize> JsonSchema for [T; N] {
+    fn json_schema() -> Schema {
+        json!({ "type": "array", "items": T::json_schema(), "minItems": N, "maxItems": N })
+    }
+}
+
+impl<T: JsonSchema, S> JsonSchema for HashSet<T, S> {
+    fn json_schema() -> Schema {
+        json!({ "type": "array", "items": T::json_schema(), "uniqueItems": true })
+    }
+}
+
+impl<T: JsonSchema> JsonSchema for BTreeSet<T> {
+    fn json_schema() -> Schema {
+        HashSet::<T>::json_schema()
+    }
+}
+
+impl<K, V: JsonSchema, S> JsonSchema for HashMap<K, V, S> {
+    fn json_schema() -> Schema {
+        json!({ "type": "object", "additionalProperties": V::json_schema() })
+    }
+}
+
+impl<K, V: JsonSchema> JsonSchema for BTreeMap<K, V> {
+    fn json_schema() -> Schema {
+        HashMap::<K, V>::json_schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_lose_nested_defaults() {
+        let schema = secret(json!({
+            "type": "object",
+            "default": { "password": "changeme" },
+            "properties": {
+                "default": { "type": "string", "default": "x" },
+                "password": { "type": "string", "default": "changeme" },
+                "hosts": { "items": { "anyOf": [{ "default": 1 }] } },
+            },
+        }));
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "writeOnly": true,
+                "properties": {
+                    "default": { "type": "string" },
+                    "password": { "type": "string" },
+                    "hosts": { "items": { "anyOf": [{}] } },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn range_bounds() {
+        let mut schema = u16::json_schema();
+        range(&mut schema, 1..=1024);
+        assert_eq!(schema["minimum"], 1);
+        assert_eq!(schema["maximum"], 1024);
+
+        let mut schema = f64::json_schema();
+        range(&mut schema, 0.0..1.0);
+        assert_eq!(schema["minimum"], 0.0);
+        assert_eq!(schema["exclusiveMaximum"], 1.0);
+    }
+
+    #[test]
+    fn lengths_follow_the_type() {
+        let mut schema = String::json_schema();
+        min_len(&mut schema, 1);
+        assert_eq!(schema["minLength"], 1);
+
+        let mut schema = Vec::<String>::json_schema();
+        max_len(&mut schema, 4);
+        assert_eq!(schema["maxItems"], 4);
+
+        let mut schema = bool::json_schema();
+        min_len(&mut schema, 1);
+        assert_eq!(schema, bool::json_schema());
+    }
+}
@@ -0,0 +1,67 @@
+use crate::FromFile;
+
+/// Conversion of a resolved configuration back into its shadow, with every
+/// field set.
+///
+/// Implemented automatically by `#[derive(FromFile)]`, recursing into nested
+/// `FromFile` structs, together with `From<&T>` for the shadow, if every
+/// field type implements it. Leaf values (any `FromFile` type whose shadow
+/// is itself) are cloned; other hand-written `FromFile` types need a manual
+/// implementation to be part of a derived one.
+pub trait ToShadow: FromFile {
+    fn to_shadow(&self) -> Self::Shadow;
+
+    /// The shadow of what `Self` resolves to without a file, or `None` if it
+    /// needs one.
+    ///
+    /// Derived implementations resolve it field by field, so that a
+    /// `#[from_file(required)]` field only leaves itself unset.
+    #[must_use]
+    fn default_shadow() -> Option<Self::Shadow>
+    where
+        Self: Sized,
+    {
+        Self::try_from_file(None)
+            .ok()
+            .map(|value| value.to_shadow())
+    }
+
+    /// Like [`ToShadow::to_shadow`], but leaves every `#[from_file(secret)]`
+    /// field unset, so the result can be logged or shared.
+    fn to_redacted_shadow(&self) -> Self::Shadow {
+        self.to_shadow()
+    }
+}
+
+impl<T> ToShadow for T
+where
+    T: FromFile<Shadow = T> + Clone,
+{
+    fn to_shadow(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// Conversion of a resolved configuration into a shadow that only sets what
+/// differs from the defaults.
+///
+/// Implemented by `#[derive(FromFile)]` if every field type implements it and
+/// `PartialEq`, which [`ToShadow`] itself does not need. Leaf values are
+/// cloned whole.
+pub trait MinimalShadow: ToShadow {
+    /// Like [`ToShadow::to_shadow`], but leaves unset every field equal to
+    /// the value it would get without one: its `#[from_file(default = ...)]`
+    /// expression, or what `from_file(None)` gives its type.
+    ///
+    /// Serializing the result writes only what differs from the defaults.
+    fn to_minimal_shadow(&self) -> Self::Shadow;
+}
+
+impl<T> MinimalShadow for T
+where
+    T: FromFile<Shadow = T> + Clone,
+{
+    fn to_minimal_shadow(&self) -> Self {
+        self.clone()
+    }
+}
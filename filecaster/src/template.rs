@@ -0,0 +1,277 @@
+//! Commented example configuration files generated from a derived type.
+
+use crate::ToShadow;
+use crate::diff::join_path;
+use crate::meta::{FieldMeta, StructMeta};
+use crate::value::Value;
+use serde::Serialize;
+
+/// Format of a [`Template`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TemplateFormat {
+    Toml,
+    Yaml,
+    Json5,
+}
+
+impl TemplateFormat {
+    const fn comment(self) -> &'static str {
+        match self {
+            Self::Toml | Self::Yaml => "#",
+            Self::Json5 => "//",
+        }
+    }
+}
+
+/// Generates an example configuration file for a type deriving `FromFile`:
+/// every key, preceded by its doc comment and set to its default value, with
+/// nested structs as sections.
+///
+/// Defaults are resolved field by field. Fields without one, such as
+/// `#[from_file(required)]` ones, and `#[from_file(secret)]` fields are
+/// written commented out without a value.
+///
+/// ```rust,ignore
+/// print!("{}", Template::new(TemplateFormat::Toml).render::<AppConfig>());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Template {
+    format: TemplateFormat,
+    comment_out: bool,
+}
+
+impl Template {
+    #[must_use]
+    pub const fn new(format: TemplateFormat) -> Self {
+        Self {
+            format,
+            comment_out: false,
+        }
+    }
+
+    /// Writes every key commented out, so the file documents the defaults
+    /// without pinning them.
+    #[must_use]
+    pub const fn comment_out_defaults(mut self) -> Self {
+        self.comment_out = true;
+        self
+    }
+
+    /// Renders the template of `T`.
+    #[must_use]
+    pub fn render<T>(&self) -> String
+    where
+        T: ToShadow,
+        T::Shadow: Serialize,
+    {
+        let meta = T::meta().unwrap_or_else(|| StructMeta::new(Vec::new()));
+        let defaults = T::default_shadow().and_then(|shadow| Value::from_serialize(&shadow).ok());
+        let mut blocks = Vec::new();
+        if let Some(doc) = meta.doc {
+            blocks.push(self.doc(doc, ""));
+        }
+        match self.format {
+            TemplateFormat::Toml => blocks.extend(self.toml(&meta, defaults.as_ref(), "", false)),
+            TemplateFormat::Yaml => blocks.extend(self.nested(&meta, defaults.as_ref(), "", false)),
+            TemplateFormat::Json5 => {
+                let fields = self.nested(&meta, defaults.as_ref(), "  ", false);
+                blocks.push(format!("{{\n{}\n}}", fields.join("\n\n")));
+            }
+        }
+        blocks.join("\n\n") + "\n"
+    }
+
+    /// TOML keys of `meta`, followed by its nested structs as `[sections]`.
+    fn toml(
+        &self,
+        meta: &StructMeta,
+        value: Option<&Value>,
+        path: &str,
+        secret: bool,
+    ) -> Vec<String> {
+        let (sections, leaves): (Vec<_>, Vec<_>) =
+            meta.fields.iter().partition(|field| field.nested.is_some());
+        let mut blocks = leaves
+            .into_iter()
+            .map(|field| self.leaf(field, get(value, field.name), "", secret))
+            .collect::<Vec<_>>();
+        for field in sections {
+            let Some(nested) = &field.nested else {
+                continue;
+            };
+            let path = join_path(path, &key(field.name));
+            let mut children = self.toml(
+                nested,
+                get(value, field.name),
+                &path,
+                secret || field.secret,
+            );
+            let mut header = format!("{}[{path}]", self.field_doc(field, ""));
+            if !children.is_empty() {
+                header = format!("{header}\n{}", children.remove(0));
+            }
+            blocks.push(header);
+            blocks.extend(children);
+        }
+        blocks
+    }
+
+    /// YAML or JSON5 keys of `meta`, with nested structs inline.
+    fn nested(
+        &self,
+        meta: &StructMeta,
+        value: Option<&Value>,
+        indent: &str,
+        secret: bool,
+    ) -> Vec<String> {
+        meta.fields
+            .iter()
+            .map(|field| {
+                let value = get(value, field.name);
+                let Some(nested) = &field.nested else {
+                    return self.leaf(field, value, indent, secret);
+                };
+                let inner = format!("{indent}  ");
+                let children = self
+                    .nested(nested, value, &inner, secret || field.secret)
+                    .join("\n\n");
+                let doc = self.field_doc(field, indent);
+                let name = key(field.name);
+                match self.format {
+                    TemplateFormat::Json5 => {
+                        format!("{doc}{indent}{name}: {{\n{children}\n{indent}}},")
+                    }
+                    _ => format!("{doc}{indent}{name}:\n{children}"),
+                }
+            })
+            .collect()
+    }
+
+    /// `secret` tells whether the field lies below a secret one.
+    fn leaf(&self, field: &FieldMeta, value: Option<&Value>, indent: &str, secret: bool) -> String {
+        let name = key(field.name);
+        let value = value.filter(|_| !secret && !field.secret);
+        let text = value.map(|value| self.value(value)).unwrap_or_default();
+        let line = match (self.format, text.is_empty()) {
+            (TemplateFormat::Toml, false) => format!("{name} = {text}"),
+            (TemplateFormat::Toml, true) => format!("{name} ="),
+            (TemplateFormat::Yaml, false) => format!("{name}: {text}"),
+            (TemplateFormat::Yaml, true) => format!("{name}:"),
+            (TemplateFormat::Json5, false) => format!("{name}: {text},"),
+            (TemplateFormat::Json5, true) => format!("{name}:"),
+        };
+        let comment = if self.comment_out || value.is_none() {
+            format!("{} ", self.format.comment())
+        } else {
+            String::new()
+        };
+        format!("{}{indent}{comment}{line}", self.field_doc(field, indent))
+    }
+
+    /// The doc comment of `field` followed by a line break, if it has one.
+    fn field_doc(&self, field: &FieldMeta, indent: &str) -> String {
+        field
+            .doc
+            .map(|doc| format!("{}\n", self.doc(doc, indent)))
+            .unwrap_or_default()
+    }
+
+    fn doc(&self, doc: &str, indent: &str) -> String {
+        let comment = self.format.comment();
+        doc.lines()
+            .map(|line| {
+                if line.is_empty() {
+                    format!("{indent}{comment}")
+                } else {
+                    format!("{indent}{comment} {line}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// `value` as an inline value.
+    fn value(&self, value: &Value) -> String {
+        match value {
+            Value::Null => "null".to_string(),
+            Value::Bool(v) => v.to_string(),
+            Value::Integer(v) => v.to_string(),
+            Value::Unsigned(v) => v.to_string(),
+            Value::Float(v) => self.float(*v),
+            Value::String(v) => quote(v),
+            Value::List(list) => {
+                let items = list
+                    .iter()
+                    .filter(|item| **item != Value::Null || self.format != TemplateFormat::Toml)
+                    .map(|item| self.value(item))
+                    .collect::<Vec<_>>();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Map(map) if map.is_empty() => "{}".to_string(),
+            Value::Map(map) => {
+                let separator = match self.format {
+                    TemplateFormat::Toml => " = ",
+                    _ => ": ",
+                };
+                let entries = map
+                    .iter()
+                    .filter(|(_, value)| **value != Value::Null)
+                    .map(|(name, value)| format!("{}{separator}{}", key(name), self.value(value)))
+                    .collect::<Vec<_>>();
+                format!("{{ {} }}", entries.join(", "))
+            }
+        }
+    }
+
+    fn float(&self, v: f64) -> String {
+        if v.is_finite() {
+            return format!("{v:?}");
+        }
+        let sign = if v.is_sign_negative() { "-" } else { "" };
+        match (self.format, v.is_nan()) {
+            (TemplateFormat::Toml, true) => "nan".to_string(),
+            (TemplateFormat::Toml, false) => format!("{sign}inf"),
+            (TemplateFormat::Yaml, true) => ".nan".to_string(),
+            (TemplateFormat::Yaml, false) => format!("{sign}.inf"),
+            (TemplateFormat::Json5, true) => "NaN".to_string(),
+            (TemplateFormat::Json5, false) => format!("{sign}Infinity"),
+        }
+    }
+}
+
+/// The non-null entry `name` of a map.
+fn get<'v>(value: Option<&'v Value>, name: &str) -> Option<&'v Value> {
+    match value? {
+        Value::Map(map) => map.get(name).filter(|value| **value != Value::Null),
+        _ => None,
+    }
+}
+
+/// `name` as a key, quoted unless it is a plain identifier.
+fn key(name: &str) -> String {
+    let bare = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if bare { name.to_string() } else { quote(name) }
+}
+
+/// A double-quoted string with JSON escapes, which TOML, YAML and JSON5 all
+/// accept.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04X}", u32::from(c))),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
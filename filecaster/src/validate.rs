@@ -0,0 +1,504 @@
+//! Checks behind the `#[from_file(...)]` validation attributes.
+//!
+//! Derived implementations run the field checks on every resolved value,
+//! after defaults are applied, and the struct-level constraints once the
+//! whole struct, nested ones included, is resolved. Failures are reported
+//! with the field's or struct's path. The checks can also be called from
+//! hand-written [`FromFile::try_from_file`](crate::FromFile::try_from_file)
+//! implementations.
+
+use crate::{Error, REDACTED};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{self, Debug, Display};
+use std::ops::RangeBounds;
+#[cfg(feature = "regex")]
+use std::sync::OnceLock;
+
+#[cfg(feature = "regex")]
+pub use regex::Regex;
+
+/// Values with a length, checked by `min_len`, `max_len` and `non_empty`.
+///
+/// Strings are measured in characters, collections in elements.
+pub trait Length {
+    fn length(&self) -> usize;
+}
+
+impl Length for str {
+    fn length(&self) -> usize {
+        self.chars().count()
+    }
+}
+
+impl Length for String {
+    fn length(&self) -> usize {
+        self.as_str().length()
+    }
+}
+
+impl<T> Length for [T] {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for Vec<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for VecDeque<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V, S> Length for HashMap<K, V, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<K, V> Length for BTreeMap<K, V> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T, S> Length for HashSet<T, S> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T> Length for BTreeSet<T> {
+    fn length(&self) -> usize {
+        self.len()
+    }
+}
+
+/// `#[from_file(range = 1..=65535)]`: `value` lies within `range`.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if `value` is out of range.
+pub fn range<T, R>(value: &T, range: R) -> Result<(), Error>
+where
+    T: PartialOrd + Debug,
+    R: RangeBounds<T> + Debug,
+{
+    check_range(value, range, false)
+}
+
+fn check_range<T, R>(value: &T, range: R, secret: bool) -> Result<(), Error>
+where
+    T: PartialOrd + Debug,
+    R: RangeBounds<T> + Debug,
+{
+    if range.contains(value) {
+        Ok(())
+    } else {
+        let value = shown(value, secret);
+        Err(Error::invalid(format!("{value} is not in {range:?}")))
+    }
+}
+
+/// `#[from_file(min_len = 1)]`: `value` has at least `min` elements.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if `value` is too short.
+pub fn min_len<T: Length + ?Sized>(value: &T, min: usize) -> Result<(), Error> {
+    let len = value.length();
+    if len >= min {
+        Ok(())
+    } else {
+        Err(Error::invalid(format!(
+            "length {len} is less than the minimum of {min}"
+        )))
+    }
+}
+
+/// `#[from_file(max_len = 64)]`: `value` has at most `max` elements.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if `value` is too long.
+pub fn max_len<T: Length + ?Sized>(value: &T, max: usize) -> Result<(), Error> {
+    let len = value.length();
+    if len <= max {
+        Ok(())
+    } else {
+        Err(Error::invalid(format!(
+            "length {len} is more than the maximum of {max}"
+        )))
+    }
+}
+
+/// `#[from_file(non_empty)]`: `value` has at least one element.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if `value` is empty.
+pub fn non_empty<T: Length + ?Sized>(value: &T) -> Result<(), Error> {
+    if value.length() > 0 {
+        Ok(())
+    } else {
+        Err(Error::invalid("must not be empty"))
+    }
+}
+
+/// `#[from_file(one_of = ["debug", "info"])]`: `value` equals one of `options`.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if `value` is not among `options`.
+pub fn one_of<T, U>(value: &T, options: &[U]) -> Result<(), Error>
+where
+    T: PartialEq<U> + Debug,
+    U: Debug,
+{
+    check_one_of(value, options, false)
+}
+
+fn check_one_of<T, U>(value: &T, options: &[U], secret: bool) -> Result<(), Error>
+where
+    T: PartialEq<U> + Debug,
+    U: Debug,
+{
+    if options.iter().any(|option| value == option) {
+        Ok(())
+    } else {
+        let value = shown(value, secret);
+        Err(Error::invalid(format!("{value} is not one of {options:?}")))
+    }
+}
+
+/// `#[from_file(regex = "^[a-z]+$")]`: `value` matches `regex`.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if `value` does not match.
+#[cfg(feature = "regex")]
+pub fn regex(value: &str, regex: &Regex) -> Result<(), Error> {
+    check_regex(value, regex, false)
+}
+
+#[cfg(feature = "regex")]
+fn check_regex(value: &str, regex: &Regex, secret: bool) -> Result<(), Error> {
+    if regex.is_match(value) {
+        Ok(())
+    } else {
+        let value = shown(value, secret);
+        Err(Error::invalid(format!("{value} does not match `{regex}`")))
+    }
+}
+
+/// `pattern` compiled once into `cell`. Derived implementations keep a
+/// `static` cell per field, and reject invalid patterns at build time.
+///
+/// # Panics
+///
+/// Panics if `pattern` is not a valid regular expression.
+#[cfg(feature = "regex")]
+#[doc(hidden)]
+pub fn compiled<'a>(cell: &'a OnceLock<Regex>, pattern: &str) -> &'a Regex {
+    cell.get_or_init(|| Regex::new(pattern).expect("pattern checked by derive(FromFile)"))
+}
+
+/// Fields that may be left unset, checked by `exactly_one_of` and `requires`.
+pub trait Presence {
+    fn is_present(&self) -> bool;
+}
+
+impl<T> Presence for Option<T> {
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl Presence for bool {
+    fn is_present(&self) -> bool {
+        *self
+    }
+}
+
+/// `#[from_file(exactly_one_of(a, b))]`: exactly one of the named fields is set.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] if none or several of `fields` are set.
+pub fn exactly_one_of(fields: &[(&str, bool)]) -> Result<(), Error> {
+    let set = fields
+        .iter()
+        .filter(|(_, set)| *set)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    let all = fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    match set.len() {
+        1 => Ok(()),
+        0 => Err(Error::invalid(format!(
+            "exactly one of {} must be set, but none is",
+            names(&all)
+        ))),
+        _ => Err(Error::invalid(format!(
+            "exactly one of {} must be set, but {} are",
+            names(&all),
+            names(&set)
+        ))),
+    }
+}
+
+/// `#[from_file(requires(a, b, c))]`: if `a` is set, so are `b` and `c`.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] naming the required fields that are not set.
+pub fn requires(field: (&str, bool), required: &[(&str, bool)]) -> Result<(), Error> {
+    let missing = required
+        .iter()
+        .filter(|(_, set)| !set)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    if !field.1 || missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::invalid(format!(
+            "`{}` requires {} to be set",
+            field.0,
+            names(&missing)
+        )))
+    }
+}
+
+/// Operator of a `#[from_file(check(a <= b))]` constraint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+        })
+    }
+}
+
+/// `#[from_file(check(a <= b))]`: two fields compare as stated.
+///
+/// # Errors
+///
+/// Returns [`Error::Field`] showing both values if the comparison fails.
+pub fn compare<T>(lhs: (&str, &T), op: Comparison, rhs: (&str, &T)) -> Result<(), Error>
+where
+    T: PartialOrd + Debug + ?Sized,
+{
+    check_compare(lhs, op, rhs, |_| false)
+}
+
+/// `secret` tells whether the field at a path is secret; it is only asked
+/// once the comparison fails.
+fn check_compare<T>(
+    lhs: (&str, &T),
+    op: Comparison,
+    rhs: (&str, &T),
+    secret: impl Fn(&str) -> bool,
+) -> Result<(), Error>
+where
+    T: PartialOrd + Debug + ?Sized,
+{
+    let (a, b) = (lhs.1, rhs.1);
+    let holds = match op {
+        Comparison::Lt => a < b,
+        Comparison::Le => a <= b,
+        Comparison::Gt => a > b,
+        Comparison::Ge => a >= b,
+        Comparison::Eq => a == b,
+        Comparison::Ne => a != b,
+    };
+    if holds {
+        Ok(())
+    } else {
+        let (a, b) = (shown(a, secret(lhs.0)), shown(b, secret(rhs.0)));
+        Err(Error::invalid(format!(
+            "`{}` ({a}) must be {op} `{}` ({b})",
+            lhs.0, rhs.0
+        )))
+    }
+}
+
+/// The checks that show the value, masking it with [`REDACTED`] for
+/// `#[from_file(secret)]` fields.
+#[doc(hidden)]
+pub mod redacted {
+    #[cfg(feature = "regex")]
+    use super::Regex;
+    use super::{Comparison, Debug, Error, RangeBounds};
+    use crate::FromFile;
+
+    pub fn range<T, R>(value: &T, range: R) -> Result<(), Error>
+    where
+        T: PartialOrd + Debug,
+        R: RangeBounds<T> + Debug,
+    {
+        super::check_range(value, range, true)
+    }
+
+    pub fn one_of<T, U>(value: &T, options: &[U]) -> Result<(), Error>
+    where
+        T: PartialEq<U> + Debug,
+        U: Debug,
+    {
+        super::check_one_of(value, options, true)
+    }
+
+    #[cfg(feature = "regex")]
+    pub fn regex(value: &str, regex: &Regex) -> Result<(), Error> {
+        super::check_regex(value, regex, true)
+    }
+
+    /// Like [`super::compare`], masking each side that lies in or below a
+    /// secret field of `S`.
+    pub fn compare<S, T>(lhs: (&str, &T), op: Comparison, rhs: (&str, &T)) -> Result<(), Error>
+    where
+        S: FromFile,
+        T: PartialOrd + Debug + ?Sized,
+    {
+        super::check_compare(lhs, op, rhs, |path| {
+            S::meta().is_some_and(|meta| meta.is_secret(path))
+        })
+    }
+}
+
+/// `value` as shown in messages: its `Debug` output, or [`REDACTED`].
+fn shown<T: Debug + ?Sized>(value: &T, secret: bool) -> String {
+    if secret {
+        REDACTED.to_string()
+    } else {
+        format!("{value:?}")
+    }
+}
+
+fn names(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|name| format!("`{name}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(result: Result<(), Error>) -> String {
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn range_bounds() {
+        assert!(range(&80_u16, 1..=65535).is_ok());
+        assert!(range(&0.5_f64, 0.0..1.0).is_ok());
+        assert_eq!(
+            message(range(&0_u16, 1..=65535)),
+            "invalid value: 0 is not in 1..=65535"
+        );
+        assert!(range(&10, ..10).is_err());
+    }
+
+    #[test]
+    fn lengths() {
+        assert!(min_len("héllo", 5).is_ok());
+        assert!(max_len("héllo", 5).is_ok());
+        assert!(min_len(&vec![1, 2], 3).is_err());
+        assert_eq!(
+            message(max_len(&String::from("abc"), 2)),
+            "invalid value: length 3 is more than the maximum of 2"
+        );
+        assert!(non_empty(&HashMap::<u8, u8>::new()).is_err());
+        assert!(non_empty("x").is_ok());
+    }
+
+    #[test]
+    fn one_of_options() {
+        assert!(one_of(&String::from("info"), &["debug", "info"]).is_ok());
+        assert_eq!(
+            message(one_of(&3, &[1, 2])),
+            "invalid value: 3 is not one of [1, 2]"
+        );
+    }
+
+    #[test]
+    fn exactly_one() {
+        assert!(exactly_one_of(&[("a", true), ("b", false)]).is_ok());
+        assert_eq!(
+            message(exactly_one_of(&[("a", false), ("b", false)])),
+            "invalid value: exactly one of `a`, `b` must be set, but none is"
+        );
+        assert_eq!(
+            message(exactly_one_of(&[("a", true), ("b", true), ("c", false)])),
+            "invalid value: exactly one of `a`, `b`, `c` must be set, but `a`, `b` are"
+        );
+    }
+
+    #[test]
+    fn requirements() {
+        assert!(requires(("key", false), &[("cert", false)]).is_ok());
+        assert!(requires(("key", true), &[("cert", true)]).is_ok());
+        assert_eq!(
+            message(requires(("key", true), &[("cert", false), ("ca", true)])),
+            "invalid value: `key` requires `cert` to be set"
+        );
+    }
+
+    #[test]
+    fn comparisons() {
+        assert!(compare(("min", &1), Comparison::Le, ("max", &1)).is_ok());
+        assert!(compare(("min", &1), Comparison::Lt, ("max", &1)).is_err());
+        assert_eq!(
+            message(compare(("min", &5), Comparison::Le, ("max", &3))),
+            "invalid value: `min` (5) must be <= `max` (3)"
+        );
+    }
+
+    #[test]
+    fn redacted_values() {
+        assert_eq!(
+            message(redacted::range(&0_u16, 1..=65535)),
+            "invalid value: *** is not in 1..=65535"
+        );
+        assert_eq!(
+            message(redacted::one_of(&"hunter2", &["a", "b"])),
+            r#"invalid value: *** is not one of ["a", "b"]"#
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_patterns() {
+        static CELL: OnceLock<Regex> = OnceLock::new();
+        let pattern = compiled(&CELL, "^[a-z]+$");
+        assert!(regex("abc", pattern).is_ok());
+        assert_eq!(
+            message(regex("ABC", pattern)),
+            r#"invalid value: "ABC" does not match `^[a-z]+$`"#
+        );
+        assert!(std::ptr::eq(pattern, compiled(&CELL, "^[a-z]+$")));
+        assert_eq!(
+            message(redacted::regex("Hunter2", pattern)),
+            "invalid value: *** does not match `^[a-z]+$`"
+        );
+    }
+}
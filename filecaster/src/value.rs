@@ -0,0 +1,829 @@
+//! Format-agnostic document tree used to layer configuration sources.
+
+mod ser;
+
+use crate::error::prefix_path;
+use crate::{Error, FromFile, ToShadow};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess,
+    Visitor,
+};
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize, forward_to_deserialize_any};
+use std::collections::BTreeMap;
+use std::fmt::{self, Display};
+
+/// The entries of a [`Value::Map`], sorted by key.
+pub type Map = BTreeMap<String, Value>;
+
+/// A parsed configuration document, independent of its source format.
+///
+/// [`Loader`](crate::Loader) parses every file into a `Value` and merges
+/// them before resolving the result. Custom sources can build one directly,
+/// from scratch or from any shadow or resolved configuration, and turn it
+/// back into either:
+///
+/// ```rust,ignore
+/// let mut value = Value::from_config(&AppConfig::from_file(None))?;
+/// value.merge([("port".to_string(), Value::Integer(9000))].into_iter().collect());
+/// let config: AppConfig = value.into_config()?;
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Integer(i64),
+    /// An integer above `i64::MAX`; smaller ones are always `Integer`.
+    Unsigned(u64),
+    Float(f64),
+    String(String),
+    List(Vec<Self>),
+    Map(Map),
+}
+
+impl Value {
+    /// Deep-merges `other` into `self`; maps are merged key by key, anything
+    /// else in `other` replaces the current value.
+    pub fn merge(&mut self, other: Self) {
+        match (self, other) {
+            (Self::Map(base), Self::Map(over)) => {
+                for (key, value) in over {
+                    match base.get_mut(&key) {
+                        Some(existing) => existing.merge(value),
+                        None => {
+                            base.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    /// Deserializes this tree into `T`, such as a shadow, reporting the
+    /// dotted path of the first value that does not fit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value does not fit the type it is deserialized
+    /// into.
+    pub fn deserialize_into<T: DeserializeOwned>(self) -> Result<T, DeError> {
+        T::deserialize(self)
+    }
+
+    /// Builds a tree from any `Serialize` value, such as a shadow.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for values a tree cannot hold, such as integers
+    /// beyond the range of `i64` or maps with keys that are not strings.
+    pub fn from_serialize<T: Serialize + ?Sized>(value: &T) -> Result<Self, DeError> {
+        value.serialize(ser::ValueSerializer)
+    }
+
+    /// Builds the tree of a resolved configuration, with every field set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a field cannot be represented, as for
+    /// [`Value::from_serialize`].
+    pub fn from_config<T>(config: &T) -> Result<Self, DeError>
+    where
+        T: ToShadow,
+        T::Shadow: Serialize,
+    {
+        Self::from_serialize(&config.to_shadow())
+    }
+
+    /// Resolves this tree into `T`, through its shadow and
+    /// [`FromFile::try_from_file`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Field`] for a value that does not fit its field, or
+    /// whatever `try_from_file` reports.
+    pub fn into_config<T>(self) -> Result<T, Error>
+    where
+        T: FromFile,
+        T::Shadow: DeserializeOwned,
+    {
+        T::try_from_file(Some(self.deserialize_into()?))
+    }
+
+    /// Like [`Value::deserialize_into`], but coerces strings into the type
+    /// being deserialized where possible: `"8080"` or `"0x1F90"` into a
+    /// number, `"yes"` into `true`, `"a, b"` into a list, and so on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value does not fit even after coercion.
+    pub fn deserialize_lenient<T: DeserializeOwned>(self) -> Result<T, DeError> {
+        T::deserialize(Lenient(self))
+    }
+
+    /// `v` as an `Integer`, or as `Unsigned` if it does not fit.
+    fn unsigned(v: u64) -> Self {
+        i64::try_from(v).map_or(Self::Unsigned(v), Self::Integer)
+    }
+
+    const fn kind(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Bool(_) => "boolean",
+            Self::Integer(_) | Self::Unsigned(_) => "integer",
+            Self::Float(_) => "float",
+            Self::String(_) => "string",
+            Self::List(_) => "list",
+            Self::Map(_) => "map",
+        }
+    }
+}
+
+/// Error produced while deserializing a [`Value`], carrying the path of the
+/// offending entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeError {
+    pub path: String,
+    pub message: String,
+}
+
+impl DeError {
+    fn at(mut self, segment: &str) -> Self {
+        self.path = prefix_path(segment, &self.path);
+        self
+    }
+}
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "`{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for DeError {}
+
+impl From<DeError> for Error {
+    fn from(error: DeError) -> Self {
+        Self::Field {
+            path: error.path,
+            message: error.message,
+            location: None,
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Self {
+        Self::Bool(v)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Self::Integer(v)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(v: f64) -> Self {
+        Self::Float(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Self::String(v.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Self::String(v)
+    }
+}
+
+impl From<Vec<Self>> for Value {
+    fn from(list: Vec<Self>) -> Self {
+        Self::List(list)
+    }
+}
+
+impl From<Map> for Value {
+    fn from(map: Map) -> Self {
+        Self::Map(map)
+    }
+}
+
+impl<V: Into<Self>> FromIterator<(String, V)> for Value {
+    fn from_iter<I: IntoIterator<Item = (String, V)>>(entries: I) -> Self {
+        Self::Map(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            path: String::new(),
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Map entries holding `Null` are skipped, so documents can be written to
+/// formats without a null value (such as TOML); for a shadow an absent key
+/// and an explicit null are equivalent.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(v) => serializer.serialize_bool(*v),
+            Self::Integer(v) => serializer.serialize_i64(*v),
+            Self::Unsigned(v) => serializer.serialize_u64(*v),
+            Self::Float(v) => serializer.serialize_f64(*v),
+            Self::String(v) => serializer.serialize_str(v),
+            Self::List(list) => serializer.collect_seq(list),
+            Self::Map(map) => {
+                let mut entries = serializer.serialize_map(None)?;
+                for (key, value) in map {
+                    if *value != Self::Null {
+                        entries.serialize_entry(key, value)?;
+                    }
+                }
+                entries.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any configuration value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Integer(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::unsigned(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut list = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            list.push(item);
+        }
+        Ok(Value::List(list))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut access: A) -> Result<Value, A::Error> {
+        let mut map = Map::new();
+        while let Some((key, value)) = access.next_entry()? {
+            map.insert(key, value);
+        }
+        Ok(Value::Map(map))
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visit(self, visitor, false)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Self::Null => visitor.visit_none(),
+            other => visitor.visit_some(other),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            Self::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Self::Map(map) if map.len() == 1 => {
+                let deserializer = de::value::MapDeserializer::new(map.into_iter());
+                visitor.visit_enum(de::value::MapAccessDeserializer::new(deserializer))
+            }
+            other => Err(de::Error::custom(format!(
+                "invalid type: {}, expected an enum variant",
+                other.kind()
+            ))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Hands `value` to `visitor`; lists and maps deserialize their children
+/// leniently if `lenient` is set.
+fn visit<'de, V: Visitor<'de>>(
+    value: Value,
+    visitor: V,
+    lenient: bool,
+) -> Result<V::Value, DeError> {
+    match value {
+        Value::Null => visitor.visit_unit(),
+        Value::Bool(v) => visitor.visit_bool(v),
+        Value::Integer(v) => visitor.visit_i64(v),
+        Value::Unsigned(v) => visitor.visit_u64(v),
+        Value::Float(v) => visitor.visit_f64(v),
+        Value::String(v) => visitor.visit_string(v),
+        Value::List(list) => visitor.visit_seq(ListAccess {
+            iter: list.into_iter().enumerate(),
+            lenient,
+        }),
+        Value::Map(map) => visitor.visit_map(EntryAccess {
+            iter: map.into_iter(),
+            key: None,
+            value: None,
+            lenient,
+        }),
+    }
+}
+
+/// Deserializer behind [`Value::deserialize_lenient`], for sources such as
+/// environment variables where every value is a string.
+struct Lenient(Value);
+
+macro_rules! lenient_integer {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                match &self.0 {
+                    Value::String(text) => match parse_integer(text) {
+                        Some(n) => visit_integer(n, visitor),
+                        None => self.deserialize_any(visitor),
+                    },
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for Lenient {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visit(self.0, visitor, true)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match &self.0 {
+            Value::String(text) => match parse_bool(text) {
+                Some(v) => visitor.visit_bool(v),
+                None => self.deserialize_any(visitor),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    lenient_integer! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_f64(visitor)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match &self.0 {
+            Value::String(text) => match text.trim().replace('_', "").parse() {
+                Ok(v) => visitor.visit_f64(v),
+                Err(_) => self.deserialize_any(visitor),
+            },
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::String(text) => {
+                let items = text
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect::<Vec<_>>();
+                visit(Value::List(items), visitor, true)
+            }
+            other => visit(other, visitor, true),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(Self(other)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.0.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+/// Parses `"yes"`/`"no"`, `"on"`/`"off"`, `"1"`/`"0"` and `"true"`/`"false"`,
+/// ignoring case.
+fn parse_bool(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a decimal, `0x` hexadecimal, `0o` octal or `0b` binary integer,
+/// with optional sign and `_` separators.
+fn parse_integer(text: &str) -> Option<i128> {
+    let text = text.trim().replace('_', "");
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(&text)),
+    };
+    let (radix, digits) = match digits.get(..2) {
+        Some("0x" | "0X") => (16, &digits[2..]),
+        Some("0o" | "0O") => (8, &digits[2..]),
+        Some("0b" | "0B") => (2, &digits[2..]),
+        _ => (10, digits),
+    };
+    if digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let magnitude = i128::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+/// Visits `n` with the narrowest method that holds it, so the visitor's own
+/// range check reports overflows.
+fn visit_integer<'de, V: Visitor<'de>>(n: i128, visitor: V) -> Result<V::Value, DeError> {
+    if let Ok(n) = i64::try_from(n) {
+        visitor.visit_i64(n)
+    } else if let Ok(n) = u64::try_from(n) {
+        visitor.visit_u64(n)
+    } else {
+        visitor.visit_i128(n)
+    }
+}
+
+impl IntoDeserializer<'_, DeError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct ListAccess {
+    iter: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    lenient: bool,
+}
+
+impl<'de> SeqAccess<'de> for ListAccess {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        let Some((index, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+        let result = if self.lenient {
+            seed.deserialize(Lenient(value))
+        } else {
+            seed.deserialize(value)
+        };
+        result.map(Some).map_err(|e| e.at(&format!("[{index}]")))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct EntryAccess {
+    iter: std::collections::btree_map::IntoIter<String, Value>,
+    key: Option<String>,
+    value: Option<Value>,
+    lenient: bool,
+}
+
+impl<'de> MapAccess<'de> for EntryAccess {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+        let result = seed.deserialize(key.as_str().into_deserializer()).map(Some);
+        self.key = Some(key);
+        self.value = Some(value);
+        result
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let key = self.key.take().unwrap_or_default();
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| <DeError as de::Error>::custom("value requested before key"))?;
+        let result = if self.lenient {
+            seed.deserialize(Lenient(value))
+        } else {
+            seed.deserialize(value)
+        };
+        result.map_err(|e| e.at(&key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Inner {
+        port: Option<u16>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Outer {
+        name: Option<String>,
+        inner: Option<Inner>,
+        tags: Option<Vec<u8>>,
+    }
+
+    pub fn map(entries: impl IntoIterator<Item = (&'static str, Value)>) -> Value {
+        Value::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn merge_is_deep_for_maps() {
+        let mut base = map([
+            ("name", Value::String("a".into())),
+            ("inner", map([("port", Value::Integer(1))])),
+        ]);
+        base.merge(map([("inner", map([("host", Value::String("h".into()))]))]));
+        assert_eq!(
+            base,
+            map([
+                ("name", Value::String("a".into())),
+                (
+                    "inner",
+                    map([
+                        ("host", Value::String("h".into())),
+                        ("port", Value::Integer(1))
+                    ])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn merge_replaces_non_maps() {
+        let mut base = map([("tags", Value::List(vec![Value::Integer(1)]))]);
+        base.merge(map([("tags", Value::List(vec![Value::Integer(2)]))]));
+        assert_eq!(base, map([("tags", Value::List(vec![Value::Integer(2)]))]));
+    }
+
+    #[test]
+    fn deserialize_nested() {
+        let value = map([
+            ("name", Value::String("x".into())),
+            ("inner", map([("port", Value::Integer(80))])),
+            ("tags", Value::Null),
+        ]);
+        let outer: Outer = value.deserialize_into().unwrap();
+        assert_eq!(
+            outer,
+            Outer {
+                name: Some("x".into()),
+                inner: Some(Inner { port: Some(80) }),
+                tags: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_error_carries_path() {
+        let value = map([("inner", map([("port", Value::String("x".into()))]))]);
+        let err = value.deserialize_into::<Outer>().unwrap_err();
+        assert_eq!(err.path, "inner.port");
+
+        let value = map([(
+            "tags",
+            Value::List(vec![Value::Integer(1), Value::Integer(-1)]),
+        )]);
+        let err = value.deserialize_into::<Outer>().unwrap_err();
+        assert_eq!(err.path, "tags[1]");
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Flags {
+        enabled: Option<bool>,
+        ratio: Option<f32>,
+        mask: Option<u64>,
+        offset: Option<i32>,
+    }
+
+    #[test]
+    fn lenient_coerces_strings() {
+        let value = map([
+            ("name", Value::String("x".into())),
+            ("inner", map([("port", Value::String("8080".into()))])),
+            ("tags", Value::String("1, 2,3".into())),
+        ]);
+        let outer: Outer = value.clone().deserialize_lenient().unwrap();
+        assert_eq!(outer.inner, Some(Inner { port: Some(8080) }));
+        assert_eq!(outer.tags, Some(vec![1, 2, 3]));
+        assert!(value.deserialize_into::<Outer>().is_err());
+
+        let value = map([
+            ("enabled", Value::String("Yes".into())),
+            ("ratio", Value::String("0.5".into())),
+            ("mask", Value::String("0xFF_FF".into())),
+            ("offset", Value::String("-1_000".into())),
+        ]);
+        assert_eq!(
+            value.deserialize_lenient::<Flags>().unwrap(),
+            Flags {
+                enabled: Some(true),
+                ratio: Some(0.5),
+                mask: Some(0xFFFF),
+                offset: Some(-1000),
+            }
+        );
+    }
+
+    #[test]
+    fn lenient_reports_what_does_not_parse() {
+        let value = map([("inner", map([("port", Value::String("high".into()))]))]);
+        let err = value.deserialize_lenient::<Outer>().unwrap_err();
+        assert_eq!(err.path, "inner.port");
+        let value = map([("inner", map([("port", Value::String("70000".into()))]))]);
+        assert!(value.deserialize_lenient::<Outer>().is_err());
+        let value = map([("tags", Value::String("1,x".into()))]);
+        let err = value.deserialize_lenient::<Outer>().unwrap_err();
+        assert_eq!(err.path, "tags[1]");
+    }
+
+    #[derive(Debug, Serialize)]
+    enum Mode {
+        Fast,
+        Limit(u8),
+        Window { from: u8, to: u8 },
+    }
+
+    #[derive(Debug, Serialize)]
+    struct Snapshot {
+        name: Option<String>,
+        skipped: Option<u8>,
+        modes: Vec<Mode>,
+        weights: BTreeMap<u8, f32>,
+    }
+
+    #[test]
+    fn from_serialize_builds_a_tree() {
+        let snapshot = Snapshot {
+            name: Some("x".into()),
+            skipped: None,
+            modes: vec![Mode::Fast, Mode::Limit(3), Mode::Window { from: 1, to: 2 }],
+            weights: BTreeMap::from([(1, 0.5)]),
+        };
+        assert_eq!(
+            Value::from_serialize(&snapshot).unwrap(),
+            map([
+                ("name", Value::String("x".into())),
+                ("skipped", Value::Null),
+                (
+                    "modes",
+                    Value::List(vec![
+                        Value::String("Fast".into()),
+                        map([("Limit", Value::Integer(3))]),
+                        map([(
+                            "Window",
+                            map([("from", Value::Integer(1)), ("to", Value::Integer(2))])
+                        )]),
+                    ])
+                ),
+                ("weights", map([("1", Value::Float(0.5))])),
+            ])
+        );
+        assert_eq!(
+            Value::from_serialize(&[u64::MAX, 1]).unwrap(),
+            Value::List(vec![Value::Unsigned(u64::MAX), Value::Integer(1)])
+        );
+        let err = Value::from_serialize(&BTreeMap::from([("big", vec![u128::MAX])])).unwrap_err();
+        assert_eq!(err.path, "big[0]");
+    }
+
+    #[test]
+    fn parse_literals() {
+        assert_eq!(parse_integer("0b101"), Some(5));
+        assert_eq!(parse_integer("0o17"), Some(15));
+        assert_eq!(parse_integer("+42"), Some(42));
+        assert_eq!(parse_integer("-0x10"), Some(-16));
+        assert_eq!(parse_integer("--1"), None);
+        assert_eq!(parse_integer("0x"), None);
+        assert_eq!(parse_bool(" OFF "), Some(false));
+        assert_eq!(parse_bool("maybe"), None);
+    }
+}
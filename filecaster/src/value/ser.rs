@@ -0,0 +1,519 @@
+//! Serializer building a [`Value`] from any `Serialize` type.
+
+use super::{DeError, Map, Value};
+use serde::ser::{self, Impossible, Serialize};
+
+impl ser::Error for DeError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        <Self as serde::de::Error>::custom(msg)
+    }
+}
+
+fn out_of_range(v: impl std::fmt::Display) -> DeError {
+    <DeError as serde::de::Error>::custom(format!("integer `{v}` is out of range"))
+}
+
+pub struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = DeError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = Variant<SerializeList>;
+    type SerializeMap = SerializeEntries;
+    type SerializeStruct = SerializeEntries;
+    type SerializeStructVariant = Variant<SerializeEntries>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, DeError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, DeError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, DeError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, DeError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, DeError> {
+        Ok(Value::Integer(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, DeError> {
+        i64::try_from(v)
+            .map(Value::Integer)
+            .or_else(|_| u64::try_from(v).map(Value::Unsigned))
+            .map_err(|_| out_of_range(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, DeError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, DeError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, DeError> {
+        Ok(Value::Integer(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, DeError> {
+        Ok(Value::unsigned(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, DeError> {
+        u64::try_from(v)
+            .map(Value::unsigned)
+            .map_err(|_| out_of_range(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, DeError> {
+        Ok(Value::Float(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, DeError> {
+        Ok(Value::Float(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, DeError> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, DeError> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, DeError> {
+        Ok(Value::List(
+            v.iter().map(|&b| Value::Integer(b.into())).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Value, DeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, DeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, DeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, DeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, DeError> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, DeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, DeError> {
+        let mut map = Map::new();
+        map.insert(variant.to_string(), value.serialize(self)?);
+        Ok(Value::Map(map))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, DeError> {
+        Ok(SerializeList(Vec::with_capacity(len.unwrap_or_default())))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, DeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, DeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Variant<SerializeList>, DeError> {
+        Ok(Variant {
+            name: variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeEntries, DeError> {
+        Ok(SerializeEntries {
+            map: Map::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeEntries, DeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Variant<SerializeEntries>, DeError> {
+        Ok(Variant {
+            name: variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+pub struct SerializeList(Vec<Value>);
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), DeError> {
+        let index = self.0.len();
+        self.0.push(
+            value
+                .serialize(ValueSerializer)
+                .map_err(|e| e.at(&format!("[{index}]")))?,
+        );
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), DeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        Ok(Value::List(self.0))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), DeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        Ok(Value::List(self.0))
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), DeError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        Ok(Value::List(self.0))
+    }
+}
+
+pub struct SerializeEntries {
+    map: Map,
+    key: Option<String>,
+}
+
+impl SerializeEntries {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), DeError> {
+        let value = value.serialize(ValueSerializer).map_err(|e| e.at(&key))?;
+        self.map.insert(key, value);
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeEntries {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), DeError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), DeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <DeError as ser::Error>::custom("value serialized before key"))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        Ok(Value::Map(self.map))
+    }
+}
+
+impl ser::SerializeStruct for SerializeEntries {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        Ok(Value::Map(self.map))
+    }
+}
+
+/// An enum variant holding a tuple or struct, serialized as a map with a
+/// single entry named after the variant.
+pub struct Variant<S> {
+    name: &'static str,
+    inner: S,
+}
+
+impl Variant<SerializeList> {
+    fn end(self) -> Value {
+        let mut map = Map::new();
+        map.insert(self.name.to_string(), Value::List(self.inner.0));
+        Value::Map(map)
+    }
+}
+
+impl ser::SerializeTupleVariant for Variant<SerializeList> {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), DeError> {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        Ok(Self::end(self))
+    }
+}
+
+impl ser::SerializeStructVariant for Variant<SerializeEntries> {
+    type Ok = Value;
+    type Error = DeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), DeError> {
+        self.inner.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value, DeError> {
+        let mut map = Map::new();
+        map.insert(self.name.to_string(), Value::Map(self.inner.map));
+        Ok(Value::Map(map))
+    }
+}
+
+/// Serializes map keys, which must be strings or plain scalars.
+struct KeySerializer;
+
+fn key_must_be_a_string() -> DeError {
+    <DeError as ser::Error>::custom("map keys must be strings")
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = DeError;
+    type SerializeSeq = Impossible<String, DeError>;
+    type SerializeTuple = Impossible<String, DeError>;
+    type SerializeTupleStruct = Impossible<String, DeError>;
+    type SerializeTupleVariant = Impossible<String, DeError>;
+    type SerializeMap = Impossible<String, DeError>;
+    type SerializeStruct = Impossible<String, DeError>;
+    type SerializeStructVariant = Impossible<String, DeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, DeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<String, DeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, DeError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, DeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, DeError> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, DeError> {
+        Err(key_must_be_a_string())
+    }
+}
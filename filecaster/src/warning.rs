@@ -0,0 +1,76 @@
+use crate::diagnostic::Location;
+use crate::error::write_unknown_key;
+use std::fmt::{self, Display};
+use std::path::PathBuf;
+
+/// A problem found while loading that did not stop the configuration from
+/// being resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// A key that no field accepts, in a struct warning about unknown keys.
+    UnknownKey {
+        path: String,
+        suggestion: Option<String>,
+        location: Option<Box<Location>>,
+    },
+    /// A deprecated key, such as the former name of a renamed field.
+    Deprecated {
+        path: String,
+        /// The key that replaces it, if it is an alias.
+        replacement: Option<String>,
+        note: Option<String>,
+        /// The file that used the key.
+        origin: String,
+        location: Option<Box<Location>>,
+    },
+    /// The resolved configuration could not be written to the cache file
+    /// set with `Loader::cache_file`.
+    Cache { path: PathBuf, message: String },
+}
+
+impl Warning {
+    /// Where in its source this warning was detected, if known.
+    #[must_use]
+    pub fn location(&self) -> Option<&Location> {
+        match self {
+            Self::UnknownKey { location, .. } | Self::Deprecated { location, .. } => {
+                location.as_deref()
+            }
+            Self::Cache { .. } => None,
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownKey {
+                path, suggestion, ..
+            } => write_unknown_key(f, path, suggestion.as_deref()),
+            Self::Deprecated {
+                path,
+                replacement,
+                note,
+                origin,
+                ..
+            } => {
+                write!(f, "deprecated key `{path}` in {origin}")?;
+                if let Some(replacement) = replacement {
+                    write!(f, " (now `{replacement}`)")?;
+                }
+                match note {
+                    Some(note) => write!(f, ": {note}"),
+                    None => Ok(()),
+                }
+            }
+            Self::Cache { path, message } => {
+                write!(f, "failed to write cache `{}`: {message}", path.display())
+            }
+        }?;
+        match self.location() {
+            Some(location) => write!(f, "\n{location}"),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+#![cfg(all(feature = "json", feature = "toml"))]
+
+mod common;
+
+use common::write_file;
+use filecaster::{Error, FromFile, Loader};
+
+/// A port number that rejects `0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Port(u16);
+
+impl FromFile for Port {
+    type Shadow = u16;
+
+    fn from_file(file: Option<Self::Shadow>) -> Self {
+        Self::try_from_file(file).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_from_file(file: Option<Self::Shadow>) -> Result<Self, Error> {
+        match file.unwrap_or(80) {
+            0 => Err(Error::invalid("port must not be 0")),
+            port => Ok(Self(port)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    port: Port,
+    admin_port: Port,
+    workers: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct User {
+    #[from_file(required)]
+    name: String,
+    retries: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    server: Server,
+    user: User,
+}
+
+fn into_errors(error: Error) -> Vec<Error> {
+    match error {
+        Error::Multiple { errors } => errors,
+        error => vec![error],
+    }
+}
+
+#[test]
+fn derived_try_from_file_reports_every_field() {
+    let error = Config::try_from_file(Some(ConfigFile {
+        server: Some(ServerFile {
+            port: Some(0),
+            admin_port: Some(0),
+            workers: None,
+        }),
+        user: None,
+    }))
+    .unwrap_err();
+    let messages = into_errors(error)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "invalid value for `server.port`: port must not be 0",
+            "invalid value for `server.admin_port`: port must not be 0",
+            "missing required fields: `user.name`",
+        ]
+    );
+}
+
+#[test]
+fn loader_reports_errors_from_every_layer_and_stage() {
+    let base = write_file(".toml", "[server]\nworkers = \"many\"\nport = 0\n");
+    let user = write_file(".json", r#"{ "user": { "retries": -1 } }"#);
+    let error = Loader::<Config>::new()
+        .file(base.path())
+        .file(user.path())
+        .load()
+        .unwrap_err();
+    let errors = into_errors(error);
+    assert_eq!(errors.len(), 4, "{errors:#?}");
+
+    let tagged = errors
+        .iter()
+        .map(|error| match error {
+            Error::Field { path, .. } => (
+                path.clone(),
+                error.location().map(|location| location.origin.clone()),
+            ),
+            Error::Missing { paths } => (paths.join(","), None),
+            other => panic!("unexpected error: {other:?}"),
+        })
+        .collect::<Vec<_>>();
+    let base_origin = Some(base.path().display().to_string());
+    let user_origin = Some(user.path().display().to_string());
+    assert_eq!(
+        tagged,
+        vec![
+            ("server.workers".to_string(), base_origin.clone()),
+            ("user.retries".to_string(), user_origin),
+            ("server.port".to_string(), base_origin),
+            ("user.name".to_string(), None),
+        ]
+    );
+}
+
+#[test]
+fn invalid_required_value_is_not_also_reported_missing() {
+    let file = write_file(".toml", "[user]\nname = 42\n");
+    let error = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let Error::Field { path, .. } = &error else {
+        panic!("expected a single field error, got {error:?}");
+    };
+    assert_eq!(path, "user.name");
+}
+
+#[test]
+fn every_broken_source_is_reported() {
+    let first = write_file(".toml", "server = ");
+    let second = write_file(".json", "{ nope }");
+    let error = Loader::<Config>::new()
+        .file(first.path())
+        .file(second.path())
+        .load()
+        .unwrap_err();
+    let errors = into_errors(error);
+    assert_eq!(errors.len(), 2);
+    assert!(
+        errors
+            .iter()
+            .all(|error| matches!(error, Error::Parse { .. }))
+    );
+}
+
+#[test]
+fn aggregated_display_lists_each_error() {
+    let file = write_file(".toml", "[server]\nport = 0\nadmin_port = 0\n");
+    let error = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let text = error.to_string();
+    assert!(text.starts_with("3 configuration errors:\n- invalid value for `server.port`"));
+    assert!(text.contains("\n   --> "));
+    assert!(text.ends_with("- missing required fields: `user.name`"));
+}
+
+#[test]
+fn multiple_flattens_and_unwraps() {
+    let single = Error::multiple(vec![Error::Multiple {
+        errors: vec![Error::invalid("bad")],
+    }]);
+    assert!(matches!(single, Error::Field { .. }));
+
+    let merged = Error::multiple(vec![
+        Error::Missing {
+            paths: vec!["a".to_string()],
+        },
+        Error::invalid("bad").at("c"),
+        Error::Missing {
+            paths: vec!["b".to_string(), "c".to_string()],
+        },
+    ]);
+    let errors = into_errors(merged);
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(&errors[0], Error::Missing { paths } if paths == &["a", "b"]));
+}
@@ -0,0 +1,10 @@
+use std::io::Write;
+use tempfile::{Builder, NamedTempFile};
+
+/// A temporary file with the given extension (e.g. `".toml"`) holding
+/// `content`.
+pub fn write_file(suffix: &str, content: &str) -> NamedTempFile {
+    let mut tmp = Builder::new().suffix(suffix).tempfile().unwrap();
+    write!(tmp.as_file_mut(), "{content}").unwrap();
+    tmp
+}
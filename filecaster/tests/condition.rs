@@ -0,0 +1,95 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use common::write_file;
+use filecaster::{Error, FromFile, Loader};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(default = 8080)]
+    port: u16,
+    #[from_file(default = "info")]
+    log_level: String,
+    workers: u8,
+}
+
+const CONFIG: &str = r#"
+port = 80
+workers = 2
+
+[profile.prod]
+log_level = "warn"
+
+[when.'hostname = "web-*"']
+workers = 16
+
+[when.'hostname = "db-*"']
+workers = 4
+
+[when.'all(hostname = "web-*", profile = "prod")']
+log_level = "error"
+"#;
+
+fn load(hostname: &str, profile: Option<&str>) -> Config {
+    let file = write_file(".toml", CONFIG);
+    let mut loader = Loader::<Config>::new()
+        .file(file.path())
+        .conditional_sections()
+        .hostname(hostname);
+    if let Some(profile) = profile {
+        loader = loader.profile(profile);
+    }
+    loader.load().unwrap()
+}
+
+#[test]
+fn hostname_glob_selects_section() {
+    assert_eq!(load("web-01", None).workers, 16);
+    assert_eq!(load("db-01", None).workers, 4);
+    assert_eq!(load("cache-01", None).workers, 2);
+}
+
+#[test]
+fn conditions_can_match_active_profile() {
+    let config = load("web-01", Some("prod"));
+    assert_eq!(config.log_level, "error");
+    assert_eq!(config.port, 80);
+
+    assert_eq!(load("db-01", Some("prod")).log_level, "warn");
+    assert_eq!(load("web-01", None).log_level, "info");
+}
+
+#[test]
+fn env_var_condition() {
+    let file = write_file(".toml", "[when.'env.REGION = \"eu-*\"']\nworkers = 9\n");
+    let loader = Loader::<Config>::new()
+        .file(file.path())
+        .conditional_sections();
+    let config = loader
+        .clone()
+        .env_vars([("REGION", "eu-west")])
+        .load()
+        .unwrap();
+    assert_eq!(config.workers, 9);
+    let config = loader.env_vars([("REGION", "us-east")]).load().unwrap();
+    assert_eq!(config.workers, 0);
+}
+
+#[test]
+fn invalid_condition_names_the_file() {
+    let file = write_file(".toml", "[when.'colour = \"red\"']\nworkers = 1\n");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .conditional_sections()
+        .load()
+        .unwrap_err();
+    let Error::Parse {
+        origin, message, ..
+    } = err
+    else {
+        panic!("expected a parse error, got {err:?}");
+    };
+    assert_eq!(origin, file.path().display().to_string());
+    assert!(message.contains("colour"));
+}
@@ -0,0 +1,72 @@
+use filecaster::{ConfigMeta, FromFile};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    /// Address to listen on.
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    #[from_file(required, env = "APP_PORT")]
+    port: u16,
+}
+
+/// Example application configuration.
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(alias = "labels")]
+    tags: HashMap<String, Vec<String>>,
+    server: Server,
+}
+
+#[test]
+fn describes_the_field_tree() {
+    let meta = Config::config_meta();
+    assert_eq!(meta.type_name, "Config");
+    assert_eq!(meta.doc, Some("Example application configuration."));
+    assert_eq!(Config::meta(), Some(meta.clone()));
+
+    let fields = meta.flatten();
+    let paths = fields
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["tags", "server", "server.host", "server.port"]);
+
+    let (_, tags) = fields[0];
+    assert_eq!(tags.type_name, "HashMap<String, Vec<String>>");
+    assert_eq!(tags.aliases, ["labels"]);
+
+    let (_, server) = fields[1];
+    assert_eq!(server.type_name, "Server");
+    assert_eq!(
+        server.nested.as_ref().map(|nested| nested.type_name),
+        Some("Server")
+    );
+
+    let (_, host) = fields[2];
+    assert_eq!(host.doc, Some("Address to listen on."));
+    assert_eq!(host.default, Some("\"127.0.0.1\""));
+    assert!(!host.required);
+
+    let (_, port) = fields[3];
+    assert_eq!(port.type_name, "u16");
+    assert_eq!(port.default, None);
+    assert!(port.required);
+    assert_eq!(port.env, Some("APP_PORT"));
+    assert_eq!(host.env, None);
+}
+
+#[test]
+fn default_expressions_read_like_source() {
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Timeouts {
+        #[from_file(default = std::time::Duration::from_secs(30))]
+        idle: std::time::Duration,
+    }
+
+    let meta = Timeouts::config_meta();
+    assert_eq!(
+        meta.fields[0].default,
+        Some("std::time::Duration::from_secs(30)")
+    );
+}
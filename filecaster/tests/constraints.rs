@@ -0,0 +1,116 @@
+use filecaster::{Error, FromFile};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+#[from_file(requires(key_path, cert_path))]
+pub struct Tls {
+    cert_path: Option<String>,
+    key_path: Option<String>,
+    acme: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+#[from_file(check(min_workers <= max_workers), validate = Self::check)]
+pub struct Pool {
+    #[from_file(default = 1)]
+    min_workers: u32,
+    #[from_file(default = 8)]
+    max_workers: u32,
+    #[from_file(default = 60)]
+    idle_timeout: u64,
+}
+
+impl Pool {
+    fn check(&self) -> Result<(), String> {
+        if self.max_workers > 1 && self.idle_timeout == 0 {
+            Err("`idle_timeout` must be positive with more than one worker".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+#[from_file(exactly_one_of(tls.cert_path, tls.acme))]
+pub struct Config {
+    tls: Tls,
+    pool: Pool,
+}
+
+fn messages(error: Error) -> Vec<String> {
+    match error {
+        Error::Multiple { errors } => errors.iter().map(ToString::to_string).collect(),
+        error => vec![error.to_string()],
+    }
+}
+
+fn tls(cert_path: Option<&str>, key_path: Option<&str>, acme: Option<&str>) -> TlsFile {
+    TlsFile {
+        cert_path: Some(cert_path.map(Into::into)),
+        key_path: Some(key_path.map(Into::into)),
+        acme: Some(acme.map(Into::into)),
+    }
+}
+
+#[test]
+fn satisfied_constraints_pass() {
+    let config = Config::try_from_file(Some(ConfigFile {
+        tls: Some(tls(Some("cert.pem"), Some("key.pem"), None)),
+        pool: None,
+    }))
+    .unwrap();
+    assert_eq!(config.pool.max_workers, 8);
+}
+
+#[test]
+fn exactly_one_of_sees_nested_fields() {
+    let error = Config::try_from_file(None).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid value: exactly one of `tls.cert_path`, `tls.acme` must be set, but none is"
+    );
+
+    let error = Config::try_from_file(Some(ConfigFile {
+        tls: Some(tls(Some("cert.pem"), None, Some("letsencrypt"))),
+        pool: None,
+    }))
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid value: exactly one of `tls.cert_path`, `tls.acme` must be set, but `tls.cert_path`, `tls.acme` are"
+    );
+}
+
+#[test]
+fn nested_constraints_are_reported_at_their_struct() {
+    let error = Config::try_from_file(Some(ConfigFile {
+        tls: Some(tls(None, Some("key.pem"), Some("letsencrypt"))),
+        pool: Some(PoolFile {
+            min_workers: Some(16),
+            max_workers: Some(4),
+            idle_timeout: Some(0),
+        }),
+    }))
+    .unwrap_err();
+    assert_eq!(
+        messages(error),
+        vec![
+            "invalid value for `tls`: `key_path` requires `cert_path` to be set",
+            "invalid value for `pool`: `min_workers` (16) must be <= `max_workers` (4)",
+            "invalid value for `pool`: `idle_timeout` must be positive with more than one worker",
+        ]
+    );
+}
+
+#[test]
+fn custom_struct_check_runs_on_defaults() {
+    let error = Pool::try_from_file(Some(PoolFile {
+        min_workers: None,
+        max_workers: None,
+        idle_timeout: Some(0),
+    }))
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "invalid value: `idle_timeout` must be positive with more than one worker"
+    );
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use common::write_file;
+use filecaster::{Error, FromFile, Loader, Warning};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(
+        default = 8080,
+        alias = "listen_port",
+        deprecated = "use `port` instead"
+    )]
+    port: u16,
+    #[from_file(default = "localhost", alias = "hostname")]
+    host: String,
+    #[from_file(default = false, deprecated = "keep-alive is always enabled")]
+    keep_alive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    server: Server,
+}
+
+#[test]
+fn aliases_map_onto_the_new_field_with_warnings() {
+    let file = write_file(
+        ".toml",
+        "[server]\nlisten_port = 9000\nhostname = \"example.org\"\n",
+    );
+    let (config, warnings) = Loader::<Config>::new()
+        .file(file.path())
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(config.server.port, 9000);
+    assert_eq!(config.server.host, "example.org");
+    assert_eq!(warnings.len(), 2);
+
+    let origin = file.path().display().to_string();
+    let Warning::Deprecated {
+        path,
+        replacement,
+        note,
+        origin: used_in,
+        ..
+    } = &warnings[0]
+    else {
+        panic!("unexpected warning: {:?}", warnings[0]);
+    };
+    assert_eq!(path, "server.listen_port");
+    assert_eq!(replacement.as_deref(), Some("server.port"));
+    assert_eq!(note.as_deref(), Some("use `port` instead"));
+    assert_eq!(used_in, &origin);
+    assert_eq!(warnings[0].location().unwrap().line, 2);
+    assert!(warnings[0].to_string().starts_with(&format!(
+        "deprecated key `server.listen_port` in {origin} (now `server.port`): use `port` instead\n"
+    )));
+    assert!(
+        warnings[1]
+            .to_string()
+            .starts_with("deprecated key `server.hostname` in ")
+    );
+}
+
+#[test]
+fn deprecated_fields_warn_when_used() {
+    let file = write_file(".toml", "[server]\nkeep_alive = true\n");
+    let (config, warnings) = Loader::<Config>::new()
+        .file(file.path())
+        .load_with_warnings()
+        .unwrap();
+    assert!(config.server.keep_alive);
+    assert!(matches!(
+        &warnings[..],
+        [Warning::Deprecated { path, replacement: None, .. }] if path == "server.keep_alive"
+    ));
+}
+
+#[test]
+fn old_and_new_keys_together_are_an_error() {
+    let file = write_file(".toml", "[server]\nport = 80\nlisten_port = 9000\n");
+    let error = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let Error::Field { path, message, .. } = &error else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(path, "server.listen_port");
+    assert_eq!(
+        message,
+        "cannot be combined with `server.port`, which replaces it"
+    );
+    assert_eq!(error.location().unwrap().line, 3);
+}
+
+#[test]
+fn layers_can_migrate_independently() {
+    let base = write_file(".toml", "[server]\nlisten_port = 9000\n");
+    let local = write_file(".toml", "[server]\nport = 9001\n");
+    let (config, warnings) = Loader::<Config>::new()
+        .file(base.path())
+        .file(local.path())
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(config.server.port, 9001);
+    assert_eq!(warnings.len(), 1);
+}
+
+#[test]
+fn shadow_accepts_aliases_directly() {
+    let shadow: ConfigFile = toml::from_str("[server]\nlisten_port = 9000\n").unwrap();
+    assert_eq!(Config::from_file(Some(shadow)).server.port, 9000);
+}
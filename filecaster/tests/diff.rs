@@ -0,0 +1,158 @@
+use filecaster::{ConfigDiff, Diff, FieldChange, FromFile, UNSET};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    #[from_file(default = 8080)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    server: Server,
+}
+
+fn config(name: &str, port: u16) -> Config {
+    Config::from_file(Some(ConfigFile {
+        name: Some(name.to_string()),
+        server: Some(ServerFile {
+            host: None,
+            port: Some(port),
+        }),
+    }))
+}
+
+#[test]
+fn diff_identical_is_empty() {
+    let cfg = config("app", 8080);
+    assert!(Config::diff(&cfg, &cfg.clone()).is_empty());
+}
+
+#[test]
+fn diff_reports_nested_leaf_paths() {
+    let changes = Config::diff(&config("app", 8080), &config("app", 9000));
+    assert_eq!(
+        changes,
+        vec![FieldChange {
+            path: "server.port".to_string(),
+            old: "8080".to_string(),
+            new: "9000".to_string(),
+        }]
+    );
+    assert_eq!(changes[0].to_string(), "server.port: 8080 -> 9000");
+}
+
+#[test]
+fn diff_reports_every_changed_field_in_order() {
+    let changes = Config::diff(&config("old", 1), &config("new", 2));
+    let paths = changes.iter().map(|c| c.path.as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, vec!["name", "server.port"]);
+    assert_eq!(changes[0].to_string(), r#"name: "old" -> "new""#);
+}
+
+#[test]
+fn diff_on_nested_struct_directly() {
+    let old = Server::from_file(None);
+    let new = Server::from_file(Some(ServerFile {
+        host: Some("0.0.0.0".to_string()),
+        port: None,
+    }));
+    let changes = Server::diff(&old, &new);
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "host");
+}
+
+#[test]
+fn field_change_is_within_section() {
+    let change = FieldChange {
+        path: "server.port".to_string(),
+        old: "1".to_string(),
+        new: "2".to_string(),
+    };
+    assert!(change.is_within("server"));
+    assert!(change.is_within("server.port"));
+    assert!(!change.is_within("server.po"));
+    assert!(!change.is_within("serv"));
+    assert!(!change.is_within("name"));
+}
+
+#[test]
+fn diff_shadows_reports_set_and_unset_fields() {
+    let old = ConfigFile {
+        name: Some("app".to_string()),
+        server: None,
+    };
+    let new = ConfigFile {
+        name: None,
+        server: Some(ServerFile {
+            host: None,
+            port: Some(9000),
+        }),
+    };
+    let changes = ConfigFile::diff(&old, &new);
+    assert_eq!(
+        changes,
+        vec![
+            FieldChange {
+                path: "name".to_string(),
+                old: "\"app\"".to_string(),
+                new: UNSET.to_string(),
+            },
+            FieldChange {
+                path: "server.port".to_string(),
+                old: UNSET.to_string(),
+                new: "9000".to_string(),
+            },
+        ]
+    );
+    assert!(ConfigFile::diff(&new, &new.clone()).is_empty());
+}
+
+#[test]
+fn config_diff_renders_a_report() {
+    let diff = ConfigDiff::new(&config("staging", 8080), &config("prod", 443));
+    assert_eq!(
+        diff.to_string(),
+        "2 fields changed:\n  name: \"staging\" -> \"prod\"\n  server.port: 8080 -> 443"
+    );
+    let same = ConfigDiff::new(&config("app", 1), &config("app", 1));
+    assert!(same.is_empty());
+    assert_eq!(same.to_string(), "no changes");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn config_diff_renders_json() {
+    let diff = ConfigDiff::new(&config("app", 8080), &config("app", 443));
+    let json: serde_json::Value = serde_json::from_str(&diff.to_json()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!([{ "path": "server.port", "old": "8080", "new": "443" }])
+    );
+}
+
+/// A leaf type that cannot be compared.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Opaque {
+    token: u64,
+}
+
+#[derive(Debug, Clone, FromFile)]
+pub struct Session {
+    opaque: Opaque,
+    #[from_file(default = 30)]
+    timeout: u32,
+}
+
+#[test]
+fn fields_without_partial_eq_still_derive() {
+    let session = Session::from_file(Some(SessionFile {
+        opaque: Some(Opaque { token: 7 }),
+        timeout: None,
+    }));
+    assert_eq!(session.opaque.token, 7);
+    assert_eq!(session.timeout, 30);
+}
@@ -0,0 +1,352 @@
+#![cfg(all(feature = "json", feature = "toml"))]
+
+mod common;
+
+use common::write_file;
+use filecaster::{Error, FromFile, Loader, Source, Warning};
+use tempfile::TempDir;
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    #[from_file(default = 8080)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    server: Server,
+}
+
+#[test]
+fn load_single_toml_file() {
+    let file = write_file(".toml", "name = \"app\"\n[server]\nport = 9000\n");
+    let config = Loader::<Config>::new().file(file.path()).load().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.server.host, "127.0.0.1");
+    assert_eq!(config.server.port, 9000);
+}
+
+#[test]
+fn later_files_override_earlier_ones_key_by_key() {
+    let base = write_file(
+        ".toml",
+        "name = \"base\"\n[server]\nhost = \"0.0.0.0\"\nport = 1\n",
+    );
+    let local = write_file(".json", r#"{ "server": { "port": 2 } }"#);
+    let config = Loader::<Config>::new()
+        .file(base.path())
+        .file(local.path())
+        .load()
+        .unwrap();
+    assert_eq!(config.name, "base");
+    assert_eq!(config.server.host, "0.0.0.0");
+    assert_eq!(config.server.port, 2);
+}
+
+#[test]
+fn missing_optional_file_is_skipped() {
+    let dir = TempDir::new().unwrap();
+    let config = Loader::<Config>::new()
+        .optional_file(dir.path().join("missing.toml"))
+        .load()
+        .unwrap();
+    assert_eq!(config, Config::from_file(None));
+}
+
+#[test]
+fn missing_required_file_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let err = Loader::<Config>::new()
+        .file(dir.path().join("missing.toml"))
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, Error::Io { .. }));
+}
+
+#[test]
+fn parse_error_names_the_file() {
+    let file = write_file(".toml", "name = ");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let Error::Parse { origin, .. } = err else {
+        panic!("expected a parse error, got {err:?}");
+    };
+    assert_eq!(origin, file.path().display().to_string());
+}
+
+#[test]
+fn type_mismatch_reports_field_path() {
+    let file = write_file(".json", r#"{ "server": { "port": "high" } }"#);
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let Error::Field { path, .. } = err else {
+        panic!("expected a field error, got {err:?}");
+    };
+    assert_eq!(path, "server.port");
+}
+
+#[test]
+fn lenient_loader_coerces_strings() {
+    let file = write_file(
+        ".json",
+        r#"{ "name": "app", "server": { "port": "0x1F90" } }"#,
+    );
+    assert!(Loader::<Config>::new().file(file.path()).load().is_err());
+    let config = Loader::<Config>::new()
+        .file(file.path())
+        .lenient()
+        .load()
+        .unwrap();
+    assert_eq!(config.server.port, 8080);
+
+    let file = write_file(
+        ".json",
+        r#"{ "name": "app", "server": { "port": "high" } }"#,
+    );
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .lenient()
+        .load()
+        .unwrap_err();
+    assert_eq!(err.location().unwrap().line, 1);
+}
+
+#[test]
+fn unknown_extension_is_rejected() {
+    let file = write_file(".ini", "name = app");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, Error::Parse { .. }));
+}
+
+#[test]
+fn successful_load_writes_cache_file() {
+    let dir = TempDir::new().unwrap();
+    let cache = dir.path().join("cache.toml");
+    let file = write_file(".json", r#"{ "name": "app", "server": { "port": 7 } }"#);
+    Loader::<Config>::new()
+        .file(file.path())
+        .cache_file(&cache)
+        .load()
+        .unwrap();
+
+    let cached = Loader::<Config>::new().file(&cache).load().unwrap();
+    assert_eq!(cached.name, "app");
+    assert_eq!(cached.server.port, 7);
+}
+
+#[test]
+fn corrupt_source_falls_back_to_cache() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    let loader = Loader::<Config>::new()
+        .file(&path)
+        .cache_file(dir.path().join("cache.json"));
+
+    std::fs::write(&path, "name = \"good\"").unwrap();
+    let (config, error) = loader.load_or_cached().unwrap();
+    assert_eq!(config.name, "good");
+    assert!(error.is_none());
+
+    std::fs::write(&path, "name = ").unwrap();
+    let (config, error) = loader.load_or_cached().unwrap();
+    assert_eq!(config.name, "good");
+    assert!(matches!(error, Some(Error::Parse { .. })));
+}
+
+#[test]
+fn fallback_without_cache_returns_original_error() {
+    let file = write_file(".toml", "name = ");
+    let dir = TempDir::new().unwrap();
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .cache_file(dir.path().join("never-written.json"))
+        .load_or_cached()
+        .unwrap_err();
+    assert!(matches!(err, Error::Parse { .. }));
+}
+
+#[test]
+fn unwritable_cache_is_a_warning() {
+    let file = write_file(".toml", "name = \"app\"\n");
+    let dir = TempDir::new().unwrap();
+    let loader = Loader::<Config>::new()
+        .file(file.path())
+        .cache_file(dir.path().join("missing-dir").join("cache.toml"));
+
+    let (config, warnings) = loader.load_with_warnings().unwrap();
+    assert_eq!(config.name, "app");
+    assert!(matches!(warnings.as_slice(), [Warning::Cache { .. }]));
+    assert_eq!(loader.load_or_cached().unwrap().0.name, "app");
+}
+
+#[test]
+fn fallback_to_corrupt_cache_returns_both_errors() {
+    let file = write_file(".toml", "name = ");
+    let cache = write_file(".json", "{ name");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .cache_file(cache.path())
+        .load_or_cached()
+        .unwrap_err();
+    let Error::Multiple { errors } = err else {
+        panic!("expected both errors, got {err:?}");
+    };
+    let origins = errors.iter().map(|error| match error {
+        Error::Parse { origin, .. } => origin.clone(),
+        error => panic!("expected a parse error, got {error:?}"),
+    });
+    assert_eq!(
+        origins.collect::<Vec<_>>(),
+        [
+            file.path().display().to_string(),
+            cache.path().display().to_string()
+        ]
+    );
+}
+
+#[test]
+fn type_mismatch_points_at_the_value_in_the_last_layer() {
+    let base = write_file(".json", r#"{ "server": { "port": 1 } }"#);
+    let local = write_file(".toml", "name = \"app\"\n[server]\nport = \"high\"\n");
+    let err = Loader::<Config>::new()
+        .file(base.path())
+        .file(local.path())
+        .load()
+        .unwrap_err();
+    let location = err.location().expect("field errors are located");
+    assert_eq!(location.origin, local.path().display().to_string());
+    assert_eq!((location.line, location.column), (3, 8));
+    assert_eq!(location.snippet, "port = \"high\"");
+    assert!(err.to_string().ends_with(&format!(
+        " --> {}:3:8\n  |\n3 | port = \"high\"\n  |        ^^^^^^",
+        local.path().display()
+    )));
+}
+
+#[test]
+fn json_type_mismatch_is_located() {
+    let file = write_file(".json", "{\n  \"server\": {\n    \"port\": true\n  }\n}\n");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!((location.line, location.column, location.width), (3, 13, 4));
+}
+
+#[test]
+fn syntax_errors_are_located() {
+    let toml = write_file(".toml", "name = \"app\"\nport = \n");
+    let err = Loader::<Config>::new()
+        .file(toml.path())
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, Error::Parse { .. }));
+    assert_eq!(err.location().unwrap().line, 2);
+
+    let json = write_file(".json", "{\n  \"name\": \"app\",\n  oops\n}");
+    let err = Loader::<Config>::new()
+        .file(json.path())
+        .load()
+        .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!(location.line, 3);
+    assert_eq!(location.snippet, "  oops");
+    assert!(!err.to_string().contains("at line"));
+}
+
+#[test]
+fn errors_inside_profiles_point_at_the_profile_section() {
+    let file = write_file(
+        ".toml",
+        "[server]\nport = 1\n[profile.prod.server]\nport = -1\n",
+    );
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .profile("prod")
+        .load()
+        .unwrap_err();
+    let location = err.location().unwrap();
+    assert_eq!((location.line, location.column), (4, 8));
+}
+
+#[cfg(unix)]
+#[test]
+fn cache_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let cache = dir.path().join("cache.toml");
+    let file = write_file(".toml", "name = \"app\"\n");
+    Loader::<Config>::new()
+        .file(file.path())
+        .cache_file(&cache)
+        .load()
+        .unwrap();
+    let mode = std::fs::metadata(&cache).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn debug_leaves_env_vars_out() {
+    let loader = Loader::<Config>::new().env_vars([("API_TOKEN", "hunter2")]);
+    let debug = format!("{loader:?}");
+    assert!(!debug.contains("hunter2"), "{debug}");
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Service {
+    #[from_file(default = "app")]
+    name: String,
+    #[from_file(default = 8080, env = "APP_PORT")]
+    port: u16,
+    #[from_file(env = "APP_TAGS")]
+    tags: Vec<String>,
+}
+
+#[test]
+fn env_vars_are_coerced_and_layered_over_files() {
+    let file = write_file(".toml", "name = \"svc\"\nport = 1\n");
+    let loader = Loader::<Service>::new()
+        .file(file.path())
+        .env_vars([("APP_PORT", "0x1F90"), ("APP_TAGS", "a, b")]);
+    assert_eq!(loader.load().unwrap().port, 1);
+
+    let resolved = loader.clone().env().load_resolved().unwrap();
+    assert_eq!((resolved.name.as_str(), resolved.port), ("svc", 8080));
+    assert_eq!(resolved.tags, ["a", "b"]);
+    assert_eq!(resolved.source("port"), Some(&Source::Env("APP_PORT")));
+
+    let err = loader
+        .env_vars([("APP_PORT", "high")])
+        .env()
+        .load()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for `port`: invalid type: string \"high\", expected u16 \
+         (from environment variable `APP_PORT`)"
+    );
+}
+
+#[test]
+fn leniency_is_per_layer() {
+    let file = write_file(".toml", "port = \"9000\"\n");
+    let loader = Loader::<Service>::new()
+        .file(file.path())
+        .env_vars([("APP_TAGS", "a")])
+        .env();
+    let err = loader.load().unwrap_err();
+    assert_eq!(err.location().unwrap().line, 1);
+    assert_eq!(loader.lenient().load().unwrap().port, 9000);
+}
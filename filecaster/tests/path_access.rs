@@ -0,0 +1,104 @@
+#![cfg(feature = "serde")]
+
+use filecaster::{Error, FromFile, PathAccess, Value};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Tls {
+    #[from_file(default = 443)]
+    port: u16,
+    enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    host: String,
+    tls: Tls,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    tags: Vec<String>,
+    labels: HashMap<String, String>,
+    server: Server,
+}
+
+#[test]
+fn get_reads_set_values_only() {
+    let shadow = ConfigFile {
+        server: Some(ServerFile {
+            host: Some("example.com".to_string()),
+            tls: None,
+        }),
+        ..ConfigFile::default()
+    };
+    assert_eq!(
+        shadow.get_path("server.host"),
+        Some(Value::from("example.com"))
+    );
+    assert_eq!(shadow.get_path("server.tls.port"), None);
+    assert_eq!(shadow.get_path("tags"), None);
+    assert_eq!(shadow.get_path("nope"), None);
+}
+
+#[test]
+fn set_creates_sections_and_parses_strings() {
+    let mut shadow = ConfigFile::default();
+    shadow.set_path("server.tls.port", "9443").unwrap();
+    shadow.set_path("server.tls.enabled", "yes").unwrap();
+    shadow.set_path("tags", "a, b").unwrap();
+    shadow.set_path("labels.env", "prod").unwrap();
+
+    assert_eq!(shadow.get_path("server.tls.port"), Some(Value::from(9443)));
+    assert_eq!(shadow.get_path("server.tls"), {
+        let tls = [
+            ("enabled".to_string(), Value::from(true)),
+            ("port".to_string(), Value::from(9443)),
+        ];
+        Some(tls.into_iter().collect())
+    });
+    assert_eq!(shadow.get_path("tags[1]"), Some(Value::from("b")));
+
+    let config = Config::from_file(Some(shadow));
+    assert_eq!(config.server.tls.port, 9443);
+    assert!(config.server.tls.enabled);
+    assert_eq!(config.tags, ["a", "b"]);
+    assert_eq!(config.labels["env"], "prod");
+}
+
+#[test]
+fn set_indexes_into_lists() {
+    let mut shadow = ConfigFile::default();
+    shadow.set_path("tags[0]", "a").unwrap();
+    shadow.set_path("tags[1]", "b").unwrap();
+    shadow.set_path("tags[0]", "c").unwrap();
+    assert_eq!(shadow.tags, Some(vec!["c".to_string(), "b".to_string()]));
+    assert!(shadow.set_path("tags[5]", "d").is_err());
+}
+
+#[test]
+fn set_null_unsets() {
+    let mut shadow = ConfigFile::default();
+    shadow.set_path("server.host", "example.com").unwrap();
+    shadow.set_path("server.host", Value::Null).unwrap();
+    assert_eq!(shadow.get_path("server.host"), None);
+}
+
+#[test]
+fn set_rejects_unknown_keys_and_bad_values() {
+    let mut shadow = ConfigFile::default();
+    let Err(Error::UnknownKey {
+        path, suggestion, ..
+    }) = shadow.set_path("server.tsl.port", "1")
+    else {
+        panic!("expected an unknown key");
+    };
+    assert_eq!(path, "server.tsl");
+    assert_eq!(suggestion.as_deref(), Some("tls"));
+
+    let Err(Error::Field { path, .. }) = shadow.set_path("server.tls.port", "high") else {
+        panic!("expected a field error");
+    };
+    assert_eq!(path, "server.tls.port");
+    assert_eq!(shadow.get_path("server"), None);
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use common::write_file;
+use filecaster::{FromFile, Loader};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(default = "localhost")]
+    host: String,
+    #[from_file(default = 8080)]
+    port: u16,
+    debug: bool,
+}
+
+const CONFIG: &str = r#"
+host = "base.example.com"
+
+[profile.default]
+debug = true
+
+[profile.dev]
+port = 3000
+
+[profile.prod]
+host = "prod.example.com"
+debug = false
+"#;
+
+#[test]
+fn selected_profile_is_merged_over_base_and_default() {
+    let file = write_file(".toml", CONFIG);
+    let config = Loader::<Config>::new()
+        .file(file.path())
+        .profile("prod")
+        .load()
+        .unwrap();
+    assert_eq!(config.host, "prod.example.com");
+    assert_eq!(config.port, 8080);
+    assert!(!config.debug);
+
+    let config = Loader::<Config>::new()
+        .file(file.path())
+        .profile("dev")
+        .load()
+        .unwrap();
+    assert_eq!(config.host, "base.example.com");
+    assert_eq!(config.port, 3000);
+    assert!(config.debug);
+}
+
+#[test]
+fn profile_from_env_var() {
+    let file = write_file(".toml", CONFIG);
+    let loader = Loader::<Config>::new()
+        .file(file.path())
+        .profile_env("MYAPP_PROFILE")
+        .env_vars([("MYAPP_PROFILE", "dev")]);
+    assert_eq!(loader.active_profile().as_deref(), Some("dev"));
+    assert_eq!(loader.load().unwrap().port, 3000);
+
+    let explicit = loader.clone().profile("prod");
+    assert_eq!(explicit.active_profile().as_deref(), Some("prod"));
+    assert_eq!(explicit.load().unwrap().host, "prod.example.com");
+}
+
+#[test]
+fn unset_env_var_applies_only_default_profile() {
+    let file = write_file(".toml", CONFIG);
+    let config = Loader::<Config>::new()
+        .file(file.path())
+        .profile_env("MYAPP_PROFILE")
+        .env_vars([("OTHER", "dev")])
+        .load()
+        .unwrap();
+    assert_eq!(config.host, "base.example.com");
+    assert_eq!(config.port, 8080);
+    assert!(config.debug);
+}
+
+#[test]
+fn profiles_resolve_per_file_before_layering() {
+    let base = write_file(".toml", CONFIG);
+    let local = write_file(
+        ".toml",
+        "port = 9999\n[profile.dev]\nhost = \"dev.local\"\n",
+    );
+    let config = Loader::<Config>::new()
+        .file(base.path())
+        .file(local.path())
+        .profile("dev")
+        .load()
+        .unwrap();
+    assert_eq!(config.host, "dev.local");
+    assert_eq!(config.port, 9999);
+}
+
+#[test]
+fn base_section_overrides_default_profile() {
+    let file = write_file(
+        ".toml",
+        "debug = false\n[profile.default]\ndebug = true\nport = 3000\n",
+    );
+    let config = Loader::<Config>::new()
+        .file(file.path())
+        .profile("prod")
+        .load()
+        .unwrap();
+    assert!(!config.debug);
+    assert_eq!(config.port, 3000);
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Player {
+    #[from_file(default = "none")]
+    profile: String,
+}
+
+#[test]
+fn profile_key_that_is_not_a_table_is_a_value() {
+    let file = write_file(".toml", "profile = \"gaming\"\n");
+    let player = Loader::<Player>::new()
+        .file(file.path())
+        .profile("prod")
+        .load()
+        .unwrap();
+    assert_eq!(player.profile, "gaming");
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use common::write_file;
+use filecaster::{FromFile, Loader, Source};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    #[from_file(default = 8080)]
+    port: u16,
+    timeout: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Limits {
+    #[from_file(default = 10)]
+    connections: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    server: Server,
+    #[from_file(default = LimitsFile { connections: Some(100) }.into())]
+    limits: Limits,
+}
+
+fn line(source: Option<&Source>) -> (String, usize) {
+    let Some(Source::File {
+        origin,
+        location: Some(location),
+    }) = source
+    else {
+        panic!("expected a file source, got {source:?}");
+    };
+    (origin.clone(), location.line)
+}
+
+#[test]
+fn every_leaf_has_a_source() {
+    let base = write_file(
+        ".toml",
+        "name = \"app\"\n[server]\ntimeout = 10\nport = 9000\n",
+    );
+    let prod = write_file(".toml", "[profile.prod.server]\ntimeout = 30\n");
+    let resolved = Loader::<Config>::new()
+        .file(base.path())
+        .file(prod.path())
+        .profile("prod")
+        .load_resolved()
+        .unwrap();
+    assert_eq!(resolved.server.timeout, 30);
+
+    let base_origin = base.path().display().to_string();
+    let prod_origin = prod.path().display().to_string();
+    assert_eq!(line(resolved.source("name")), (base_origin.clone(), 1));
+    assert_eq!(line(resolved.source("server.port")), (base_origin, 4));
+    assert_eq!(line(resolved.source("server.timeout")), (prod_origin, 2));
+    assert_eq!(
+        resolved.source("server.host"),
+        Some(&Source::DefaultExpr("\"127.0.0.1\""))
+    );
+    assert_eq!(resolved.source("server"), None);
+    assert_eq!(resolved.sources().count(), 5);
+}
+
+#[test]
+fn defaults_of_enclosing_fields_are_reported() {
+    let file = write_file(".toml", "name = \"app\"\n");
+    let resolved = Loader::<Config>::new()
+        .file(file.path())
+        .load_resolved()
+        .unwrap();
+    assert_eq!(resolved.limits.connections, 100);
+    let Some(Source::DefaultExpr(expr)) = resolved.source("limits.connections") else {
+        panic!(
+            "unexpected source: {:?}",
+            resolved.source("limits.connections")
+        );
+    };
+    assert!(expr.starts_with("LimitsFile"));
+    assert_eq!(resolved.source("server.timeout"), Some(&Source::Default));
+}
+
+#[test]
+fn explain_lists_one_line_per_leaf() {
+    let file = write_file(".toml", "name = \"app\"\n");
+    let report = Loader::<Config>::new()
+        .file(file.path())
+        .load_resolved()
+        .unwrap()
+        .explain();
+    let lines = report.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], format!("name: {}:1:8", file.path().display()));
+    assert_eq!(lines[1], "server.host: default `\"127.0.0.1\"`");
+    assert_eq!(lines[3], "server.timeout: Default::default()");
+    assert_eq!(lines.len(), 5);
+}
@@ -0,0 +1,99 @@
+use filecaster::{FromFile, Reference, ReferenceFormat};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    /// Address to listen on.
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    /// Port to listen on.
+    #[from_file(required, range = 1..=65535, env = "APP_PORT")]
+    port: u16,
+}
+
+/// Example application configuration.
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    /// Minimum level of logged messages.
+    #[from_file(default = "info", one_of = ["debug", "info"], alias = "log")]
+    level: String,
+    tags: Vec<String>,
+    /// HTTP server.
+    server: Server,
+}
+
+#[test]
+fn markdown_reference() {
+    let reference = Reference::new(ReferenceFormat::Markdown).render::<Config>();
+    assert_eq!(
+        reference,
+        r#"# Config
+
+Example application configuration.
+
+### `level`
+
+Minimum level of logged messages.
+
+- Type: `String`
+- Default: `"info"`
+- Constraints: `one_of = ["debug", "info"]`
+- Aliases: `log`
+
+### `tags`
+
+- Type: `Vec<String>`
+- Default: `Default::default()`
+
+## `server`
+
+HTTP server.
+
+- Type: `Server`
+
+### `server.host`
+
+Address to listen on.
+
+- Type: `String`
+- Default: `"127.0.0.1"`
+
+### `server.port`
+
+Port to listen on.
+
+- Type: `u16`
+- Required: yes
+- Environment: `APP_PORT`
+- Constraints: `range = 1..=65535`
+"#
+    );
+}
+
+#[test]
+fn roff_reference() {
+    let reference = Reference::new(ReferenceFormat::Roff)
+        .title("myapp.toml")
+        .render::<Config>();
+    assert!(reference.starts_with(
+        ".TH MYAPP.TOML 5\n\
+         .SH NAME\n\
+         myapp.toml \\- Example application configuration.\n\
+         .SH DESCRIPTION\n\
+         Example application configuration.\n\
+         .SH KEYS\n"
+    ));
+    assert!(reference.contains(".SS server\nHTTP server.\n.br\nType: Server\n"));
+    assert!(reference.ends_with(
+        ".TP\n\
+         .B server.port\n\
+         Port to listen on.\n\
+         .br\n\
+         Type: u16\n\
+         .br\n\
+         Required: yes\n\
+         .br\n\
+         Environment: APP_PORT\n\
+         .br\n\
+         Constraints: range = 1..=65535\n"
+    ));
+}
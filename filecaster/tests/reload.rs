@@ -0,0 +1,97 @@
+#![cfg(all(unix, feature = "signal", feature = "toml"))]
+
+use filecaster::{FromFile, Loader, Reloader, SharedConfig};
+use signal_hook::consts::{SIGUSR1, SIGUSR2, SIGWINCH};
+use signal_hook::low_level::raise;
+use std::fs;
+use std::sync::mpsc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(default = 8080)]
+    port: u16,
+}
+
+#[test]
+fn reload_on_signal_rereads_sources() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "port = 1").unwrap();
+
+    let loader = Loader::<Config>::new().file(&path);
+    let shared = SharedConfig::new(loader.load().unwrap());
+    assert_eq!(shared.get().port, 1);
+
+    let (tx, rx) = mpsc::channel();
+    let publish = shared.clone();
+    let handle = Reloader::new(loader)
+        .signal(SIGUSR1)
+        .spawn(move |result| {
+            publish.store(result.unwrap());
+            tx.send(()).unwrap();
+        })
+        .unwrap();
+
+    fs::write(&path, "port = 2").unwrap();
+    raise(SIGUSR1).unwrap();
+    rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(shared.get().port, 2);
+
+    handle.stop();
+}
+
+#[test]
+fn reload_errors_reach_the_callback() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "port = 1").unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    let handle = Reloader::new(Loader::<Config>::new().file(&path))
+        .signal(SIGUSR2)
+        .spawn(move |result| tx.send(result.is_err()).unwrap())
+        .unwrap();
+
+    fs::write(&path, "port = ").unwrap();
+    raise(SIGUSR2).unwrap();
+    assert!(rx.recv_timeout(Duration::from_secs(5)).unwrap());
+
+    handle.stop();
+}
+
+#[test]
+fn failed_reload_keeps_last_known_good() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("config.toml");
+    fs::write(&path, "port = 1").unwrap();
+
+    let loader = Loader::<Config>::new().file(&path);
+    let shared = SharedConfig::new(loader.load().unwrap());
+
+    let (tx, rx) = mpsc::channel();
+    let handle = Reloader::new(loader)
+        .signal(SIGWINCH)
+        .spawn_shared(shared.clone(), move |error| {
+            tx.send(error.to_string()).unwrap();
+        })
+        .unwrap();
+
+    fs::write(&path, "port = \"high\"").unwrap();
+    raise(SIGWINCH).unwrap();
+    let message = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert!(message.contains("port"));
+    assert_eq!(shared.get().port, 1);
+
+    handle.stop();
+}
+
+#[test]
+fn shared_config_snapshots_survive_store() {
+    let shared = SharedConfig::new(Config { port: 1 });
+    let snapshot = shared.get();
+    shared.store(Config { port: 2 });
+    assert_eq!(snapshot.port, 1);
+    assert_eq!(shared.get().port, 2);
+}
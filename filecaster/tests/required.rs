@@ -0,0 +1,83 @@
+use filecaster::{Error, FromFile};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Database {
+    #[from_file(required)]
+    url: String,
+    #[from_file(default = 4)]
+    pool_size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(required)]
+    name: String,
+    database: Database,
+    replica: Database,
+}
+
+fn database(url: &str) -> DatabaseFile {
+    DatabaseFile {
+        url: Some(url.to_string()),
+        pool_size: None,
+    }
+}
+
+fn missing_paths(error: Error) -> Vec<String> {
+    match error {
+        Error::Missing { paths } => paths,
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn required_fields_present() {
+    let config = Config::try_from_file(Some(ConfigFile {
+        name: Some("app".to_string()),
+        database: Some(database("postgres://primary")),
+        replica: Some(database("postgres://replica")),
+    }))
+    .unwrap();
+    assert_eq!(config.database.url, "postgres://primary");
+    assert_eq!(config.database.pool_size, 4);
+}
+
+#[test]
+fn every_missing_path_is_listed() {
+    let error = Config::try_from_file(Some(ConfigFile {
+        name: None,
+        database: Some(DatabaseFile {
+            url: None,
+            pool_size: Some(8),
+        }),
+        replica: None,
+    }))
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "missing required fields: `name`, `database.url`, `replica.url`"
+    );
+    assert_eq!(
+        missing_paths(error),
+        vec!["name", "database.url", "replica.url"]
+    );
+}
+
+#[test]
+fn empty_file_lists_all_required_fields() {
+    let error = Config::try_from_file(None).unwrap_err();
+    assert_eq!(
+        missing_paths(error),
+        vec!["name", "database.url", "replica.url"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "missing required fields: `name`")]
+fn from_file_panics_when_required_fields_are_missing() {
+    let _ = Config::from_file(Some(ConfigFile {
+        name: None,
+        database: Some(database("a")),
+        replica: Some(database("b")),
+    }));
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "schema")]
+
+use filecaster::FromFile;
+use filecaster::schema::{DIALECT, JsonSchema, json_schema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::IpAddr;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    /// Address to listen on.
+    #[from_file(default = "127.0.0.1", non_empty)]
+    host: String,
+    #[from_file(default = 8080, range = 1..=65535)]
+    port: u16,
+}
+
+/// Example application configuration.
+#[derive(Debug, Clone, PartialEq, FromFile)]
+#[from_file(unknown_keys = "deny")]
+pub struct Config {
+    #[from_file(required, min_len = 1)]
+    name: String,
+    #[from_file(default = "info", one_of = ["debug", "info"], alias = "log")]
+    level: String,
+    #[from_file(max_len = 4)]
+    tags: Vec<String>,
+    #[from_file(default = Server::from_file(Some(ServerFile { host: None, port: Some(9000) })))]
+    server: Server,
+}
+
+#[test]
+fn derived_schema() {
+    let schema = json_schema::<Config>();
+    assert_eq!(
+        schema,
+        json!({
+            "$schema": DIALECT,
+            "description": "Example application configuration.",
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["name"],
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "level": { "type": "string", "default": "info", "enum": ["debug", "info"] },
+                "log": {
+                    "type": "string",
+                    "default": "info",
+                    "enum": ["debug", "info"],
+                    "deprecated": true,
+                },
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": [],
+                    "maxItems": 4,
+                },
+                "server": {
+                    "type": "object",
+                    "properties": {
+                        "host": {
+                            "type": "string",
+                            "description": "Address to listen on.",
+                            "default": "127.0.0.1",
+                            "minLength": 1,
+                        },
+                        "port": {
+                            "type": "integer",
+                            "minimum": 1,
+                            "maximum": 65535,
+                            "default": 9000,
+                        },
+                    },
+                },
+            },
+        })
+    );
+}
+
+#[test]
+fn std_types() {
+    assert_eq!(
+        IpAddr::json_schema(),
+        json!({ "type": "string", "anyOf": [{ "format": "ipv4" }, { "format": "ipv6" }] })
+    );
+    assert_eq!(
+        Duration::json_schema()["required"],
+        json!(["secs", "nanos"])
+    );
+}
+
+/// A leaf type without a schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Mode {
+    #[default]
+    Fast,
+    Safe,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Storage {
+    mode: Mode,
+    #[from_file(default = Duration::from_secs(30))]
+    timeout: Duration,
+}
+
+#[test]
+fn fields_without_schema_still_derive() {
+    let storage = Storage::from_file(Some(StorageFile {
+        mode: Some(Mode::Safe),
+        timeout: None,
+    }));
+    assert_eq!(storage.mode, Mode::Safe);
+    assert_eq!(storage.timeout, Duration::from_secs(30));
+}
@@ -0,0 +1,199 @@
+#[cfg(feature = "toml")]
+mod common;
+
+use filecaster::{
+    ConfigDiff, ConfigMeta, Diff, FromFile, REDACTED, Reference, ReferenceFormat, ToShadow,
+};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Database {
+    #[from_file(default = "localhost")]
+    host: String,
+    /// Password of the database user.
+    #[from_file(secret, default = "changeme")]
+    password: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(secret)]
+    token: Option<String>,
+    database: Database,
+}
+
+fn config(password: &str) -> Config {
+    Config::from_file(Some(ConfigFile {
+        token: Some(Some("t0ken".to_string())),
+        database: Some(DatabaseFile {
+            host: None,
+            password: Some(password.to_string()),
+        }),
+    }))
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+#[from_file(check(old_key != new_key))]
+pub struct Rotation {
+    #[from_file(secret, one_of = ["alpha", "beta"])]
+    old_key: String,
+    #[from_file(secret, default = "alpha")]
+    new_key: String,
+}
+
+#[test]
+fn shadow_debug_hides_secrets() {
+    let shadow = config("hunter2").to_shadow();
+    let debug = format!("{shadow:?}");
+    assert!(!debug.contains("hunter2"), "{debug}");
+    assert!(!debug.contains("t0ken"), "{debug}");
+    assert_eq!(
+        debug,
+        r#"ConfigFile { token: Some("***"), database: Some(DatabaseFile { host: Some("localhost"), password: Some("***") }) }"#
+    );
+    assert_eq!(
+        format!("{:?}", DatabaseFile::default()),
+        "DatabaseFile { host: None, password: None }"
+    );
+}
+
+#[test]
+fn diffs_hide_secrets() {
+    let changes = Config::diff(&config("hunter2"), &config("swordfish"));
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].path, "database.password");
+    assert_eq!(
+        (changes[0].old.as_str(), changes[0].new.as_str()),
+        (REDACTED, REDACTED)
+    );
+
+    let old = ConfigFile::default();
+    let new = config("hunter2").to_shadow();
+    let report = ConfigDiff::new(&old, &new).to_string();
+    assert!(
+        !report.contains("hunter2") && !report.contains("t0ken"),
+        "{report}"
+    );
+    assert!(report.contains("token: <unset> -> ***"), "{report}");
+}
+
+#[test]
+fn redacted_shadows_leave_secrets_unset() {
+    let shadow = config("hunter2").to_redacted_shadow();
+    assert_eq!(shadow.token, None);
+    let database = shadow.database.unwrap();
+    assert_eq!(database.host.as_deref(), Some("localhost"));
+    assert_eq!(database.password, None);
+}
+
+#[test]
+fn metadata_marks_secrets() {
+    let meta = Config::config_meta();
+    let secrets = meta
+        .flatten()
+        .into_iter()
+        .filter(|(_, field)| field.secret)
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    assert_eq!(secrets, ["token", "database.password"]);
+
+    let reference = Reference::new(ReferenceFormat::Markdown).render::<Config>();
+    assert!(!reference.contains("changeme"), "{reference}");
+    assert!(reference.contains("- Secret: yes"), "{reference}");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn templates_leave_secrets_out() {
+    use filecaster::{Template, TemplateFormat};
+
+    let template = Template::new(TemplateFormat::Toml).render::<Database>();
+    assert_eq!(
+        template,
+        "host = \"localhost\"\n\n# Password of the database user.\n# password =\n"
+    );
+}
+
+#[cfg(feature = "schema")]
+#[test]
+fn schemas_leave_secret_defaults_out() {
+    let schema = filecaster::schema::json_schema::<Database>();
+    let password = &schema["properties"]["password"];
+    assert_eq!(password["writeOnly"], true);
+    assert!(password.get("default").is_none());
+    assert_eq!(schema["properties"]["host"]["default"], "localhost");
+
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Service {
+        #[from_file(secret)]
+        database: Database,
+    }
+
+    let schema = filecaster::schema::json_schema::<Service>();
+    let database = &schema["properties"]["database"];
+    assert_eq!(database["writeOnly"], true);
+    assert!(!schema.to_string().contains("localhost"), "{schema}");
+}
+
+#[test]
+fn validation_errors_hide_secrets() {
+    let err = Rotation::try_from_file(Some(RotationFile {
+        old_key: Some("hunter2".to_string()),
+        new_key: None,
+    }))
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"invalid value for `old_key`: *** is not one of ["alpha", "beta"]"#
+    );
+
+    let err = Rotation::try_from_file(Some(RotationFile {
+        old_key: Some("alpha".to_string()),
+        new_key: None,
+    }))
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value: `old_key` (***) must be != `new_key` (***)"
+    );
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn located_errors_hide_secrets() {
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Account {
+        #[from_file(secret)]
+        pin: u32,
+    }
+
+    let file = common::write_file(".toml", "old_key = \"Hunter2\"\n");
+    let err = filecaster::Loader::<Rotation>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let report = err.to_string();
+    assert!(!report.contains("Hunter2"), "{report}");
+    assert!(report.ends_with(&format!(" --> {}:1:11", file.path().display())));
+
+    let file = common::write_file(".toml", "pin = -5\n");
+    let err = filecaster::Loader::<Account>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "invalid value for `pin`: *** is not valid, expected u32\n --> {}:1:7",
+            file.path().display()
+        )
+    );
+
+    let file = common::write_file(".toml", "[database]\npassword = \"Hunter2\n");
+    let err = filecaster::Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    let report = err.to_string();
+    assert!(!report.contains("Hunter2"), "{report}");
+    assert_eq!(err.location().unwrap().line, 2);
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "serde")]
+
+use filecaster::{FromFile, Template, TemplateFormat};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    /// Address to listen on.
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    /// Port to listen on.
+    ///
+    /// Ports below 1024 need elevated privileges.
+    #[from_file(default = 8080)]
+    port: u16,
+}
+
+/// Example application configuration.
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    /// Name shown in logs.
+    #[from_file(default = "app")]
+    name: String,
+    tags: Vec<String>,
+    /// HTTP server.
+    server: Server,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Credentials {
+    /// Database URL.
+    #[from_file(required)]
+    url: String,
+    #[from_file(default = 5)]
+    pool: u32,
+}
+
+#[test]
+fn toml_template() {
+    let template = Template::new(TemplateFormat::Toml).render::<Config>();
+    assert_eq!(
+        template,
+        r#"# Example application configuration.
+
+# Name shown in logs.
+name = "app"
+
+tags = []
+
+# HTTP server.
+[server]
+# Address to listen on.
+host = "127.0.0.1"
+
+# Port to listen on.
+#
+# Ports below 1024 need elevated privileges.
+port = 8080
+"#
+    );
+}
+
+#[test]
+fn toml_template_parses_back_to_the_defaults() {
+    let template = Template::new(TemplateFormat::Toml).render::<Config>();
+    let file: ConfigFile = toml::from_str(&template).unwrap();
+    assert_eq!(Config::from_file(Some(file)), Config::from_file(None));
+}
+
+#[test]
+fn yaml_template() {
+    let template = Template::new(TemplateFormat::Yaml).render::<Config>();
+    assert_eq!(
+        template,
+        r#"# Example application configuration.
+
+# Name shown in logs.
+name: "app"
+
+tags: []
+
+# HTTP server.
+server:
+  # Address to listen on.
+  host: "127.0.0.1"
+
+  # Port to listen on.
+  #
+  # Ports below 1024 need elevated privileges.
+  port: 8080
+"#
+    );
+}
+
+#[test]
+fn json5_template() {
+    let template = Template::new(TemplateFormat::Json5).render::<Config>();
+    assert_eq!(
+        template,
+        r#"// Example application configuration.
+
+{
+  // Name shown in logs.
+  name: "app",
+
+  tags: [],
+
+  // HTTP server.
+  server: {
+    // Address to listen on.
+    host: "127.0.0.1",
+
+    // Port to listen on.
+    //
+    // Ports below 1024 need elevated privileges.
+    port: 8080,
+  },
+}
+"#
+    );
+}
+
+#[test]
+fn defaults_can_be_commented_out() {
+    let template = Template::new(TemplateFormat::Toml)
+        .comment_out_defaults()
+        .render::<Server>();
+    assert_eq!(
+        template,
+        r#"# Address to listen on.
+# host = "127.0.0.1"
+
+# Port to listen on.
+#
+# Ports below 1024 need elevated privileges.
+# port = 8080
+"#
+    );
+    let file: ServerFile = toml::from_str(&template).unwrap();
+    assert_eq!(Server::from_file(Some(file)), Server::from_file(None));
+}
+
+#[test]
+fn fields_without_defaults_are_commented_out() {
+    let template = Template::new(TemplateFormat::Toml).render::<Credentials>();
+    assert_eq!(template, "# Database URL.\n# url =\n\npool = 5\n");
+}
+
+#[test]
+fn defaults_are_resolved_field_by_field() {
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Service {
+        #[from_file(default = Duration::from_secs(30))]
+        timeout: Duration,
+        credentials: Credentials,
+    }
+
+    let template = Template::new(TemplateFormat::Toml).render::<Service>();
+    assert_eq!(
+        template,
+        "timeout = { nanos = 0, secs = 30 }\n\n[credentials]\n# Database URL.\n# url =\n\npool = 5\n"
+    );
+    let file: ServiceFile = toml::from_str(&template).unwrap();
+    assert_eq!(file.timeout, Some(Duration::from_secs(30)));
+}
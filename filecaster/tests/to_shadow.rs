@@ -0,0 +1,140 @@
+use filecaster::{FromFile, MinimalShadow, ToShadow};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    #[from_file(default = 8080)]
+    port: u16,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    server: Server,
+}
+
+#[test]
+fn every_field_is_set_recursively() {
+    let config = Config::from_file(None);
+    let shadow = ConfigFile::from(&config);
+    assert_eq!(shadow.name.as_deref(), Some(""));
+    let server = shadow.server.as_ref().unwrap();
+    assert_eq!(server.host.as_deref(), Some("127.0.0.1"));
+    assert_eq!(server.port, Some(8080));
+    assert_eq!(server.tags, Some(Vec::new()));
+}
+
+#[test]
+fn round_trip_preserves_the_config() {
+    let config = Config::from_file(Some(ConfigFile {
+        name: Some("app".to_string()),
+        server: Some(ServerFile {
+            host: None,
+            port: Some(9000),
+            tags: Some(vec!["a".to_string()]),
+        }),
+    }));
+    assert_eq!(Config::from_file(Some(config.to_shadow())), config);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn edited_shadow_can_be_serialized_and_reloaded() {
+    let config = Config::from_file(None);
+    let mut shadow = ConfigFile::from(&config);
+    shadow.server.as_mut().unwrap().port = Some(443);
+    let json = serde_json::to_string(&shadow).unwrap();
+    let reloaded = Config::from_file(Some(serde_json::from_str(&json).unwrap()));
+    assert_eq!(reloaded.server.port, 443);
+    assert_eq!(reloaded.server.host, "127.0.0.1");
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Limits {
+    #[from_file(default = 10)]
+    connections: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Prefs {
+    #[from_file(default = "dark")]
+    theme: String,
+    #[from_file(required)]
+    user: String,
+    server: Server,
+    #[from_file(default = LimitsFile { connections: Some(100) }.into())]
+    limits: Limits,
+}
+
+fn prefs(server_port: u16, connections: u32) -> Prefs {
+    Prefs::from_file(Some(PrefsFile {
+        theme: None,
+        user: Some("me".to_string()),
+        server: Some(ServerFile {
+            host: None,
+            port: Some(server_port),
+            tags: None,
+        }),
+        limits: Some(LimitsFile {
+            connections: Some(connections),
+        }),
+    }))
+}
+
+#[test]
+fn minimal_shadow_leaves_defaults_unset() {
+    let shadow = prefs(8080, 100).to_minimal_shadow();
+    assert_eq!(shadow.theme, None);
+    assert_eq!(shadow.user.as_deref(), Some("me"));
+    assert!(shadow.server.is_none());
+    assert!(shadow.limits.is_none());
+
+    let shadow = prefs(9000, 100).to_minimal_shadow();
+    let server = shadow.server.unwrap();
+    assert_eq!(server.port, Some(9000));
+    assert_eq!(server.host, None);
+}
+
+#[test]
+fn minimal_shadow_resolves_to_the_same_config() {
+    for config in [prefs(8080, 100), prefs(9000, 10), prefs(8080, 7)] {
+        assert_eq!(Prefs::from_file(Some(config.to_minimal_shadow())), config);
+    }
+    // Equal to the nested default but not to the field's own default.
+    let shadow = prefs(8080, 10).to_minimal_shadow();
+    assert_eq!(shadow.limits.unwrap().connections, None);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn unset_fields_are_not_serialized() {
+    let json = serde_json::to_string(&prefs(9000, 100).to_minimal_shadow()).unwrap();
+    assert_eq!(json, r#"{"user":"me","server":{"port":9000}}"#);
+}
+
+/// A leaf type that cannot be compared.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Opaque {
+    token: u64,
+}
+
+#[derive(Debug, Clone, FromFile)]
+pub struct Session {
+    opaque: Opaque,
+    #[from_file(default = 30)]
+    timeout: u32,
+}
+
+#[test]
+fn fields_without_partial_eq_still_convert_back() {
+    let session = Session::from_file(Some(SessionFile {
+        opaque: Some(Opaque { token: 7 }),
+        timeout: None,
+    }));
+    let shadow = SessionFile::from(&session);
+    assert_eq!(shadow.opaque.map(|opaque| opaque.token), Some(7));
+    assert_eq!(shadow.timeout, Some(30));
+}
@@ -0,0 +1,77 @@
+use filecaster::{Error, FromFile};
+
+/// A port number that rejects `0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Port(u16);
+
+impl FromFile for Port {
+    type Shadow = u16;
+
+    fn from_file(file: Option<Self::Shadow>) -> Self {
+        Self::try_from_file(file).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn try_from_file(file: Option<Self::Shadow>) -> Result<Self, Error> {
+        match file.unwrap_or(80) {
+            0 => Err(Error::invalid("port must not be 0")),
+            port => Ok(Self(port)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    host: String,
+    port: Port,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    server: Server,
+}
+
+fn config_file(port: u16) -> ConfigFile {
+    ConfigFile {
+        name: Some("app".to_string()),
+        server: Some(ServerFile {
+            host: None,
+            port: Some(port),
+        }),
+    }
+}
+
+#[test]
+fn try_from_file_succeeds_for_valid_values() {
+    let config = Config::try_from_file(Some(config_file(8080))).unwrap();
+    assert_eq!(config.server.port, Port(8080));
+    assert_eq!(config.name, "app");
+}
+
+#[test]
+fn try_from_file_reports_nested_field_path() {
+    let error = Config::try_from_file(Some(config_file(0))).unwrap_err();
+    match &error {
+        Error::Field { path, message, .. } => {
+            assert_eq!(path, "server.port");
+            assert_eq!(message, "port must not be 0");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert_eq!(
+        error.to_string(),
+        "invalid value for `server.port`: port must not be 0"
+    );
+}
+
+#[test]
+fn try_from_file_defaults_missing_sections() {
+    let config = Config::try_from_file(None).unwrap();
+    assert_eq!(config.server.port, Port(80));
+}
+
+#[test]
+#[should_panic(expected = "invalid value for `server.port`")]
+fn from_file_panics_on_invalid_values() {
+    let _ = Config::from_file(Some(config_file(0)));
+}
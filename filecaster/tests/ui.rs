@@ -4,4 +4,6 @@ use trybuild::TestCases;
 fn ui() {
     let t = TestCases::new();
     t.compile_fail("tests/ui/*.rs");
+    #[cfg(feature = "regex")]
+    t.compile_fail("tests/ui/regex/*.rs");
 }
@@ -0,0 +1,9 @@
+use filecaster::FromFile;
+
+#[derive(FromFile)]
+struct Config {
+    #[from_file(regex = "^[a-z")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use filecaster::FromFile;
+
+#[derive(Debug, Clone, FromFile)]
+pub struct Config {
+    #[from_file(required)]
+    name: String,
+}
+
+fn main() {
+    let _config: Config = ConfigFile::default().into();
+}
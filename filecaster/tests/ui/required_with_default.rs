@@ -0,0 +1,9 @@
+use filecaster::FromFile;
+
+#[derive(FromFile)]
+struct Config {
+    #[from_file(required, default = "localhost")]
+    host: String,
+}
+
+fn main() {}
@@ -0,0 +1,134 @@
+#![cfg(feature = "toml")]
+
+mod common;
+
+use common::write_file;
+use filecaster::{Error, FromFile, Loader, UnknownKeys, Warning};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = 8080)]
+    port: u16,
+    host: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+#[from_file(unknown_keys = "deny")]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    server: Server,
+    tls: Tls,
+}
+
+#[test]
+fn unknown_keys_are_ignored_by_default() {
+    let file = write_file(".toml", "[server]\nprot = 9000\n");
+    let (config, warnings) = Loader::<Config>::new()
+        .file(file.path())
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(config.server.port, 8080);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn warn_policy_reports_path_suggestion_and_location() {
+    let file = write_file(
+        ".toml",
+        "name = \"app\"\n[server]\nprot = 9000\nmystery = 1\n",
+    );
+    let (config, warnings) = Loader::<Config>::new()
+        .file(file.path())
+        .unknown_keys(UnknownKeys::Warn)
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(warnings.len(), 2);
+
+    let Warning::UnknownKey {
+        path, suggestion, ..
+    } = &warnings[1]
+    else {
+        panic!("unexpected warning: {:?}", warnings[1]);
+    };
+    assert_eq!(path, "server.prot");
+    assert_eq!(suggestion.as_deref(), Some("port"));
+    let location = warnings[1].location().unwrap();
+    assert_eq!(location.origin, file.path().display().to_string());
+    assert_eq!(location.line, 3);
+    assert!(
+        warnings[1]
+            .to_string()
+            .starts_with("unknown key `server.prot`, did you mean `port`?\n")
+    );
+
+    assert_eq!(
+        warnings[0].to_string().lines().next(),
+        Some("unknown key `server.mystery`")
+    );
+}
+
+#[test]
+fn deny_policy_fails_the_load() {
+    let file = write_file(".toml", "nmae = \"app\"\n");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .unknown_keys(UnknownKeys::Deny)
+        .load()
+        .unwrap_err();
+    let Error::UnknownKey {
+        path, suggestion, ..
+    } = &err
+    else {
+        panic!("expected an unknown key error, got {err:?}");
+    };
+    assert_eq!(path, "nmae");
+    assert_eq!(suggestion.as_deref(), Some("name"));
+    assert_eq!(err.location().unwrap().line, 1);
+}
+
+#[test]
+fn container_policy_overrides_loader_policy() {
+    let file = write_file(".toml", "[tls]\ncert = \"a.pem\"\nkye = \"a.key\"\n");
+    let err = Loader::<Config>::new()
+        .file(file.path())
+        .load()
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownKey { ref path, .. } if path == "tls.kye"));
+}
+
+#[test]
+fn keys_in_active_profile_are_checked() {
+    let file = write_file(".toml", "[profile.prod.server]\nhots = \"x\"\n");
+    let (_, warnings) = Loader::<Config>::new()
+        .file(file.path())
+        .profile("prod")
+        .unknown_keys(UnknownKeys::Warn)
+        .load_with_warnings()
+        .unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].location().unwrap().line, 2);
+}
+
+#[test]
+fn deny_also_applies_to_plain_deserialization() {
+    let result = toml::from_str::<TlsFile>("cert = \"a\"\nkey = \"b\"\n");
+    assert!(result.is_err());
+}
+
+#[test]
+fn meta_lists_fields_recursively() {
+    let meta = Config::meta().unwrap();
+    let names = meta.fields.iter().map(|f| f.name).collect::<Vec<_>>();
+    assert_eq!(names, vec!["name", "server", "tls"]);
+    let server = meta.field("server").unwrap().nested.as_ref().unwrap();
+    assert_eq!(server.fields.len(), 2);
+    assert!(server.field("port").unwrap().nested.is_none());
+    let tls = meta.field("tls").unwrap().nested.as_ref().unwrap();
+    assert_eq!(tls.unknown_keys, Some(UnknownKeys::Deny));
+}
@@ -0,0 +1,180 @@
+use filecaster::{Error, FromFile};
+use std::path::Path;
+
+fn absolute(path: &String) -> Result<(), String> {
+    if Path::new(path).is_absolute() {
+        Ok(())
+    } else {
+        Err(format!("`{path}` is not an absolute path"))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = 8080, range = 1..=65535)]
+    port: u32,
+    #[from_file(default = "info", one_of = ["debug", "info", "warn"])]
+    log_level: String,
+    #[from_file(default = vec!["localhost".to_string()], non_empty, max_len = 3)]
+    hosts: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    #[from_file(default = "app", min_len = 2, max_len = 16)]
+    name: String,
+    #[from_file(default = "/var/lib/app", validate = absolute)]
+    data_dir: String,
+    server: Server,
+}
+
+fn messages(error: Error) -> Vec<String> {
+    match error {
+        Error::Multiple { errors } => errors.iter().map(ToString::to_string).collect(),
+        error => vec![error.to_string()],
+    }
+}
+
+#[test]
+fn defaults_pass_validation() {
+    let config = Config::try_from_file(None).unwrap();
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.data_dir, "/var/lib/app");
+}
+
+#[test]
+fn invalid_values_are_reported_by_path() {
+    let error = Config::try_from_file(Some(ConfigFile {
+        name: Some("x".to_string()),
+        data_dir: Some("relative/dir".to_string()),
+        server: Some(ServerFile {
+            port: Some(70_000),
+            log_level: Some("loud".to_string()),
+            hosts: Some(Vec::new()),
+        }),
+    }))
+    .unwrap_err();
+    assert_eq!(
+        messages(error),
+        vec![
+            "invalid value for `name`: length 1 is less than the minimum of 2",
+            "invalid value for `data_dir`: `relative/dir` is not an absolute path",
+            "invalid value for `server.port`: 70000 is not in 1..=65535",
+            "invalid value for `server.log_level`: \"loud\" is not one of [\"debug\", \"info\", \"warn\"]",
+            "invalid value for `server.hosts`: must not be empty",
+        ]
+    );
+}
+
+#[test]
+fn every_failing_check_of_a_field_is_reported() {
+    let error = Server::try_from_file(Some(ServerFile {
+        port: None,
+        log_level: None,
+        hosts: Some(vec![String::new(); 4]),
+    }))
+    .unwrap_err();
+    assert_eq!(
+        messages(error),
+        vec!["invalid value for `hosts`: length 4 is more than the maximum of 3"]
+    );
+}
+
+#[test]
+#[should_panic(expected = "invalid value for `server.port`")]
+fn from_file_panics_on_invalid_values() {
+    let _ = Config::from_file(Some(ConfigFile {
+        name: None,
+        data_dir: None,
+        server: Some(ServerFile {
+            port: Some(0),
+            log_level: None,
+            hosts: None,
+        }),
+    }));
+}
+
+fn present(value: &Option<u32>) -> Result<(), &'static str> {
+    value.map(drop).ok_or("must be set")
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Limits {
+    #[from_file(range = 1..=64, validate = present)]
+    workers: Option<u32>,
+    #[from_file(non_empty, max_len = 8, one_of = ["primary", "replica"])]
+    role: Option<String>,
+}
+
+#[test]
+fn optional_fields_check_the_value_inside() {
+    let limits = Limits::try_from_file(Some(LimitsFile {
+        workers: Some(Some(4)),
+        role: Some(None),
+    }))
+    .unwrap();
+    assert_eq!((limits.workers, limits.role), (Some(4), None));
+
+    let error = Limits::try_from_file(Some(LimitsFile {
+        workers: Some(Some(0)),
+        role: Some(Some("backup".to_string())),
+    }))
+    .unwrap_err();
+    assert_eq!(
+        messages(error),
+        vec![
+            "invalid value for `workers`: 0 is not in 1..=64",
+            "invalid value for `role`: \"backup\" is not one of [\"primary\", \"replica\"]",
+        ]
+    );
+
+    let error = Limits::try_from_file(None).unwrap_err();
+    assert_eq!(
+        messages(error),
+        vec!["invalid value for `workers`: must be set"]
+    );
+}
+
+#[cfg(feature = "regex")]
+mod regex {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Account {
+        #[from_file(default = "admin", regex = "^[a-z_][a-z0-9_]*$")]
+        user: String,
+    }
+
+    #[test]
+    fn regex_is_checked() {
+        let ok = Account::try_from_file(None).unwrap();
+        assert_eq!(ok.user, "admin");
+        let error = Account::try_from_file(Some(AccountFile {
+            user: Some("Bad User".to_string()),
+        }))
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value for `user`: \"Bad User\" does not match `^[a-z_][a-z0-9_]*$`"
+        );
+    }
+
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Login {
+        #[from_file(regex = "^[a-z]+$")]
+        user: Option<String>,
+    }
+
+    #[test]
+    fn regex_checks_the_value_inside_an_option() {
+        assert_eq!(Login::try_from_file(None).unwrap().user, None);
+        let error = Login::try_from_file(Some(LoginFile {
+            user: Some(Some("Bad".to_string())),
+        }))
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid value for `user`: \"Bad\" does not match `^[a-z]+$`"
+        );
+    }
+}
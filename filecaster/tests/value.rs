@@ -0,0 +1,105 @@
+#![cfg(feature = "serde")]
+
+use filecaster::{Error, FromFile, Value};
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Server {
+    #[from_file(default = "127.0.0.1")]
+    host: String,
+    #[from_file(default = 8080)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, FromFile)]
+pub struct Config {
+    name: String,
+    tags: Vec<String>,
+    server: Server,
+}
+
+#[test]
+fn configs_round_trip() {
+    let config = Config::from_file(None);
+    let value = Value::from_config(&config).unwrap();
+    assert_eq!(
+        value,
+        [
+            ("name".to_string(), Value::from("")),
+            ("tags".to_string(), Value::from(Vec::new())),
+            (
+                "server".to_string(),
+                [
+                    ("host".to_string(), Value::from("127.0.0.1")),
+                    ("port".to_string(), Value::from(8080)),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        ]
+        .into_iter()
+        .collect()
+    );
+    assert_eq!(value.into_config::<Config>().unwrap(), config);
+}
+
+#[test]
+fn values_layer_over_shadows() {
+    let shadow = ConfigFile {
+        name: Some("app".to_string()),
+        ..ConfigFile::default()
+    };
+    let mut value = Value::from_serialize(&shadow).unwrap();
+    value.merge(
+        [(
+            "server".to_string(),
+            [("port".to_string(), Value::from(9000))]
+                .into_iter()
+                .collect::<Value>(),
+        )]
+        .into_iter()
+        .collect(),
+    );
+
+    let shadow = value.clone().deserialize_into::<ConfigFile>().unwrap();
+    assert_eq!(shadow.name.as_deref(), Some("app"));
+    assert_eq!(shadow.tags, None);
+
+    let config = value.into_config::<Config>().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.server.host, "127.0.0.1");
+    assert_eq!(config.server.port, 9000);
+}
+
+#[test]
+fn mistyped_values_name_their_path() {
+    let value: Value = [(
+        "server".to_string(),
+        [("port".to_string(), Value::from("high"))]
+            .into_iter()
+            .collect::<Value>(),
+    )]
+    .into_iter()
+    .collect();
+    let Err(Error::Field { path, .. }) = value.into_config::<Config>() else {
+        panic!("expected a field error");
+    };
+    assert_eq!(path, "server.port");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn large_unsigned_integers_are_kept() {
+    #[derive(Debug, Clone, PartialEq, FromFile)]
+    pub struct Limits {
+        max_bytes: u64,
+    }
+
+    let value: Value = serde_json::from_str(r#"{ "max_bytes": 18446744073709551615 }"#).unwrap();
+    let limits = value.clone().into_config::<Limits>().unwrap();
+    assert_eq!(limits.max_bytes, u64::MAX);
+    assert_eq!(Value::from_config(&limits).unwrap(), value);
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"{"max_bytes":18446744073709551615}"#
+    );
+}